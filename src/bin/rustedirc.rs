@@ -0,0 +1,166 @@
+extern crate rustedirc;
+
+use std::env;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::io;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use rustedirc::server::{Server, Config, ClientClass, Secret};
+use rustedirc::net::Daemon;
+
+const USAGE: &'static str = "\
+Usage: rustedirc [options]
+
+Options:
+    -l, --listen ADDR     listen on ADDR (ip:port), may be repeated
+    -t, --listen-tls ADDR listen for TLS connections on ADDR, may be repeated
+    -c, --cert FILE       PEM certificate for TLS listeners
+    -k, --key FILE        PEM private key for TLS listeners
+    -n, --name NAME       server name (default: irc.local)
+    -N, --network NAME    network name (default: RustedIRC)
+    -s, --sid SID         TS6 server id (default: 001)
+    -p, --pass SECRET     password clients must send with PASS, use
+                          sha256:SALT:HEX for the digest of the salt
+                          followed by the password
+    -m, --motd FILE       message of the day sent to registering clients
+    -h, --help            print this help";
+
+struct Options {
+    listen: Vec<SocketAddr>,
+    listen_tls: Vec<SocketAddr>,
+    cert: Option<PathBuf>,
+    key: Option<PathBuf>,
+    name: String,
+    network: String,
+    sid: String,
+    pass: Option<Secret>,
+    motd: Option<PathBuf>,
+}
+
+fn fail(message: &str) -> ! {
+    let _ = writeln!(io::stderr(), "rustedirc: {}\n\n{}", message, USAGE);
+    process::exit(1);
+}
+
+fn parse_options() -> Options {
+    let mut options = Options {
+        listen: Vec::new(),
+        listen_tls: Vec::new(),
+        cert: None,
+        key: None,
+        name: "irc.local".to_string(),
+        network: "RustedIRC".to_string(),
+        sid: "001".to_string(),
+        pass: None,
+        motd: None,
+    };
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || match args.next() {
+            Some(value) => value,
+            None => fail(&format!("missing value for {}", arg)),
+        };
+
+        match &arg[..] {
+            "-l" | "--listen" => {
+                let addr = value();
+                match addr.parse() {
+                    Ok(addr) => options.listen.push(addr),
+                    Err(_) => fail(&format!("invalid listen address '{}'", addr)),
+                }
+            },
+            "-t" | "--listen-tls" => {
+                let addr = value();
+                match addr.parse() {
+                    Ok(addr) => options.listen_tls.push(addr),
+                    Err(_) => fail(&format!("invalid listen address '{}'", addr)),
+                }
+            },
+            "-c" | "--cert" => options.cert = Some(PathBuf::from(value())),
+            "-k" | "--key" => options.key = Some(PathBuf::from(value())),
+            "-n" | "--name" => options.name = value(),
+            "-N" | "--network" => options.network = value(),
+            "-s" | "--sid" => options.sid = value(),
+            "-p" | "--pass" => match Secret::parse(&value()) {
+                Some(pass) => options.pass = Some(pass),
+                None => fail("hashed passwords must have the form sha256:SALT:HEX"),
+            },
+            "-m" | "--motd" => options.motd = Some(PathBuf::from(value())),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            },
+            other => fail(&format!("unknown option '{}'", other)),
+        }
+    }
+
+    if !options.listen_tls.is_empty() && (options.cert.is_none() || options.key.is_none()) {
+        fail("TLS listeners need both --cert and --key");
+    }
+    if options.listen.is_empty() && options.listen_tls.is_empty() {
+        options.listen.push("127.0.0.1:6667".parse().unwrap());
+    }
+    if options.sid.len() != 3 {
+        fail("SID must be exactly 3 characters long");
+    }
+
+    options
+}
+
+/// Reads the MOTD file, which may come with CR-LF line endings.
+fn read_motd(path: &Path) -> io::Result<Vec<String>> {
+    let file = try!(File::open(path));
+    let mut lines = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let mut line = try!(line);
+        if line.ends_with("\r") {
+            line.pop();
+        }
+        lines.push(line);
+    }
+    Ok(lines)
+}
+
+fn main() {
+    let options = parse_options();
+
+    let mut config = Config::default();
+    if let Some(ref path) = options.motd {
+        match read_motd(path) {
+            Ok(motd) => config.motd = Some(motd),
+            Err(e) => fail(&format!("cannot read MOTD from {}: {}", path.display(), e)),
+        }
+    }
+    if let Some(pass) = options.pass {
+        config.classes.push(ClientClass {
+            name: "default".to_string(),
+            hosts: vec!["*".to_string()],
+            password: Some(pass),
+        });
+    }
+
+    let server = Server::with_config(options.name, options.network, options.sid, config);
+    let mut daemon = Daemon::new(server);
+
+    for addr in options.listen.iter() {
+        if let Err(e) = daemon.listen(addr) {
+            fail(&format!("cannot listen on {}: {}", addr, e));
+        }
+    }
+
+    for addr in options.listen_tls.iter() {
+        let cert = options.cert.as_ref().unwrap();
+        let key = options.key.as_ref().unwrap();
+        if let Err(e) = daemon.listen_tls(addr, cert, key) {
+            fail(&format!("cannot listen for TLS on {}: {}", addr, e));
+        }
+    }
+
+    if let Err(e) = daemon.run() {
+        fail(&format!("event loop failed: {}", e));
+    }
+}
@@ -3,9 +3,12 @@
 
 #[cfg(test)] extern crate hamcrest;
 extern crate core;
+extern crate mio;
+extern crate time;
+extern crate openssl;
 
 pub mod message;
-// pub mod context;
+pub mod net;
 pub mod server;
 #[cfg(test)] mod server_test;
 pub mod uidgen;
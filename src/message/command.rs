@@ -0,0 +1,202 @@
+use message::Message;
+use message::error::IRCError;
+
+/// Typed form of the commands the server understands, with parameters
+/// already validated.
+#[derive(Debug, PartialEq)]
+pub enum Command<'a> {
+    Cap { subcommand: &'a str, params: Vec<&'a str> },
+    Authenticate(&'a str),
+    Pass(&'a str),
+    Nick(&'a str),
+    User { username: &'a str, mode: &'a str, realname: &'a str },
+    Quit(Option<&'a str>),
+    Ping(&'a str),
+    Pong(&'a str),
+    Privmsg { targets: Vec<&'a str>, text: &'a str },
+    Notice { targets: Vec<&'a str>, text: &'a str },
+    Join { channels: Vec<&'a str>, keys: Vec<&'a str> },
+    Part { channels: Vec<&'a str>, reason: Option<&'a str> },
+    Mode { target: &'a str, modes: Vec<&'a str> },
+    /// Any other command, left for the caller to make sense of.
+    Other(&'a str),
+}
+
+fn split_list<'a>(list: &'a str) -> Vec<&'a str> {
+    list.split(',').filter(|item| item.len() > 0).collect()
+}
+
+impl<'a> Command<'a> {
+    pub fn from_message(message: &Message<'a>) -> Result<Command<'a>, IRCError> {
+        let args = &message.arguments;
+        let command = message.command.to_uppercase();
+
+        match command.as_str() {
+            "CAP" => match args.get(0) {
+                Some(subcommand) if subcommand.len() > 0 =>
+                    Ok(Command::Cap { subcommand: *subcommand, params: args[1..].to_vec() }),
+                _ => Err(IRCError::need_more_params("CAP")),
+            },
+            "AUTHENTICATE" => match args.get(0) {
+                Some(data) if data.len() > 0 => Ok(Command::Authenticate(*data)),
+                _ => Err(IRCError::need_more_params("AUTHENTICATE")),
+            },
+            "PASS" => match args.get(0) {
+                Some(password) if password.len() > 0 => Ok(Command::Pass(*password)),
+                _ => Err(IRCError::need_more_params("PASS")),
+            },
+            "NICK" => match args.get(0) {
+                Some(nickname) if nickname.len() > 0 => Ok(Command::Nick(*nickname)),
+                _ => Err(IRCError::no_nickname_given()),
+            },
+            "USER" => if args.len() < 4 {
+                Err(IRCError::need_more_params("USER"))
+            } else {
+                Ok(Command::User { username: args[0], mode: args[1], realname: args[3] })
+            },
+            "QUIT" => Ok(Command::Quit(args.get(0).map(|reason| *reason))),
+            "PING" => match args.get(0) {
+                Some(origin) if origin.len() > 0 => Ok(Command::Ping(*origin)),
+                _ => Err(IRCError::no_origin()),
+            },
+            "PONG" => match args.get(0) {
+                Some(origin) if origin.len() > 0 => Ok(Command::Pong(*origin)),
+                _ => Err(IRCError::no_origin()),
+            },
+            "PRIVMSG" => if args.len() == 0 || split_list(args[0]).is_empty() {
+                Err(IRCError::no_recipient("PRIVMSG"))
+            } else if args.len() == 1 || args[1].len() == 0 {
+                Err(IRCError::no_text_to_send())
+            } else {
+                Ok(Command::Privmsg { targets: split_list(args[0]), text: args[1] })
+            },
+            // NOTICE must never trigger a reply.
+            "NOTICE" => if args.len() < 2 || split_list(args[0]).is_empty() || args[1].len() == 0 {
+                Err(IRCError::ignore())
+            } else {
+                Ok(Command::Notice { targets: split_list(args[0]), text: args[1] })
+            },
+            "JOIN" => if args.len() == 0 || split_list(args[0]).is_empty() {
+                Err(IRCError::need_more_params("JOIN"))
+            } else {
+                Ok(Command::Join {
+                    channels: split_list(args[0]),
+                    keys: args.get(1).map_or(vec![], |keys| split_list(*keys)),
+                })
+            },
+            "PART" => if args.len() == 0 || split_list(args[0]).is_empty() {
+                Err(IRCError::need_more_params("PART"))
+            } else {
+                Ok(Command::Part { channels: split_list(args[0]), reason: args.get(1).map(|r| *r) })
+            },
+            "MODE" => if args.len() == 0 {
+                Err(IRCError::need_more_params("MODE"))
+            } else {
+                Ok(Command::Mode { target: args[0], modes: args[1..].to_vec() })
+            },
+            _ => Ok(Command::Other(message.command)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    pub use message::Message;
+    pub use super::Command;
+    pub use hamcrest::{assert_that, is, not, none, equal_to};
+
+    pub fn parse(line: &str) -> Result<Command, String> {
+        Command::from_message(&Message::new(line).unwrap())
+            .map_err(|e| e.to_message("srv", "*").map_or("ignored".to_string(), |m| m.to_string()))
+    }
+
+    describe! command {
+        it "converts CAP" {
+            assert_that(parse("CAP REQ :sasl"), is(equal_to(Ok(Command::Cap {
+                subcommand: "REQ", params: vec!["sasl"] }))));
+        }
+
+        it "fails CAP and AUTHENTICATE without parameters" {
+            assert_that(parse("CAP"), is(equal_to(Err(":srv 461 * CAP :Not enough parameters".to_string()))));
+            assert_that(parse("AUTHENTICATE"),
+                is(equal_to(Err(":srv 461 * AUTHENTICATE :Not enough parameters".to_string()))));
+        }
+
+        it "converts NICK" {
+            assert_that(parse("NICK test"), is(equal_to(Ok(Command::Nick("test")))));
+        }
+
+        it "fails NICK without a nickname" {
+            assert_that(parse("NICK"), is(equal_to(Err(":srv 431 * :No nickname given".to_string()))));
+            assert_that(parse("NICK :"), is(equal_to(Err(":srv 431 * :No nickname given".to_string()))));
+        }
+
+        it "converts USER" {
+            assert_that(parse("USER guest 0 * :Ronnie Reagan"), is(equal_to(Ok(Command::User {
+                username: "guest", mode: "0", realname: "Ronnie Reagan" }))));
+        }
+
+        it "fails USER with missing parameters" {
+            assert_that(parse("USER guest 0 *"),
+                is(equal_to(Err(":srv 461 * USER :Not enough parameters".to_string()))));
+        }
+
+        it "fails PASS without a password" {
+            assert_that(parse("PASS"),
+                is(equal_to(Err(":srv 461 * PASS :Not enough parameters".to_string()))));
+        }
+
+        it "converts QUIT with and without reason" {
+            assert_that(parse("QUIT :bye"), is(equal_to(Ok(Command::Quit(Some("bye"))))));
+            assert_that(parse("QUIT"), is(equal_to(Ok(Command::Quit(None)))));
+        }
+
+        it "fails PING and PONG without origin" {
+            assert_that(parse("PING"), is(equal_to(Err(":srv 409 * :No origin specified".to_string()))));
+            assert_that(parse("PONG"), is(equal_to(Err(":srv 409 * :No origin specified".to_string()))));
+        }
+
+        it "converts PRIVMSG with a target list" {
+            assert_that(parse("PRIVMSG a,b :hi there"), is(equal_to(Ok(Command::Privmsg {
+                targets: vec!["a", "b"], text: "hi there" }))));
+        }
+
+        it "fails PRIVMSG without recipient" {
+            assert_that(parse("PRIVMSG"),
+                is(equal_to(Err(":srv 411 * :No recipient given (PRIVMSG)".to_string()))));
+        }
+
+        it "fails PRIVMSG without text" {
+            assert_that(parse("PRIVMSG test"), is(equal_to(Err(":srv 412 * :No text to send".to_string()))));
+            assert_that(parse("PRIVMSG test :"), is(equal_to(Err(":srv 412 * :No text to send".to_string()))));
+        }
+
+        it "silently fails a broken NOTICE" {
+            assert_that(parse("NOTICE"), is(equal_to(Err("ignored".to_string()))));
+            assert_that(parse("NOTICE test"), is(equal_to(Err("ignored".to_string()))));
+        }
+
+        it "converts JOIN with keys" {
+            assert_that(parse("JOIN #a,#b key"), is(equal_to(Ok(Command::Join {
+                channels: vec!["#a", "#b"], keys: vec!["key"] }))));
+        }
+
+        it "fails JOIN and PART without channels" {
+            assert_that(parse("JOIN"), is(equal_to(Err(":srv 461 * JOIN :Not enough parameters".to_string()))));
+            assert_that(parse("PART"), is(equal_to(Err(":srv 461 * PART :Not enough parameters".to_string()))));
+        }
+
+        it "converts MODE" {
+            assert_that(parse("MODE #chan +o test"), is(equal_to(Ok(Command::Mode {
+                target: "#chan", modes: vec!["+o", "test"] }))));
+        }
+
+        it "is case insensitive" {
+            assert_that(parse("nick test"), is(equal_to(Ok(Command::Nick("test")))));
+        }
+
+        it "passes other commands through" {
+            assert_that(parse("LIST"), is(equal_to(Ok(Command::Other("LIST")))));
+        }
+    }
+}
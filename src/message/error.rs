@@ -1,46 +1,86 @@
 // use server::{Server, Client};
 use std::{fmt, error};
 
+use message::{Reply, OwnedMessage};
+
 pub use super::numerics::rfc1459::error_replies::*;
 pub use super::numerics::rfc1459::command_replies::*;
 
 #[derive(Debug)]
 pub struct IRCError {
-    raw_args: Option<String>,
+    /// Arguments of the numeric reply following the target, None if the
+    /// error is to be ignored.
+    arguments: Option<Vec<String>>,
     error_code: u32,
 }
 
 impl IRCError {
-    pub fn new(raw_args: &str, error_code: u32) -> IRCError {
+    pub fn ignore() -> IRCError {
         IRCError {
-            raw_args: Some(raw_args.to_string()),
-            error_code: error_code,
+            arguments: None,
+            error_code: 0,
         }
     }
 
-    pub fn ignore() -> IRCError {
+    /// Wraps a numeric reply, the target is filled in when rendering.
+    pub fn from_reply(reply: Reply) -> IRCError {
         IRCError {
-            raw_args: None,
-            error_code: 0,
+            arguments: Some(reply.arguments()),
+            error_code: reply.code(),
         }
     }
 
     pub fn need_more_params(command: &str) -> IRCError {
-        IRCError {
-            raw_args: Some(format!("{} :Not enough parameters", command)),
-            error_code: ERR_NEEDMOREPARAMS,
-        }
+        IRCError::from_reply(Reply::NeedMoreParams { command: command })
     }
 
-    // pub fn to_message(&self, server: &Server, client: &Client) -> String {
-    //     format!(":{} {} {} {}", server.name(), self.error_code,
-    //         ctx.user().identifier(), self.raw_args.as_ref().unwrap())
-    // }
+    pub fn no_nickname_given() -> IRCError {
+        IRCError::from_reply(Reply::NoNicknameGiven)
+    }
+
+    pub fn no_such_nick(nick: &str) -> IRCError {
+        IRCError::from_reply(Reply::NoSuchNick { nick: nick })
+    }
+
+    pub fn no_recipient(command: &str) -> IRCError {
+        IRCError::from_reply(Reply::NoRecipient { command: command })
+    }
+
+    pub fn no_text_to_send() -> IRCError {
+        IRCError::from_reply(Reply::NoTextToSend)
+    }
+
+    pub fn no_origin() -> IRCError {
+        IRCError::from_reply(Reply::NoOrigin)
+    }
+
+    pub fn unknown_command(command: &str) -> IRCError {
+        IRCError::from_reply(Reply::UnknownCommand { command: command })
+    }
+
+    pub fn error_code(&self) -> u32 { self.error_code }
+
+    /// Whether the error is meant to be silently dropped.
+    pub fn is_ignored(&self) -> bool { self.arguments.is_none() }
+
+    /// Builds the numeric reply for the given target (the client's nickname
+    /// or `*`), or None if the error is to be ignored.
+    pub fn to_message(&self, server_name: &str, target: &str) -> Option<OwnedMessage> {
+        self.arguments.as_ref().map(|args| {
+            let mut arguments = vec![target.to_string()];
+            arguments.extend(args.iter().cloned());
+
+            OwnedMessage::new(Some(server_name.to_string()), format!("{:03}", self.error_code), arguments)
+        })
+    }
 }
 
 impl fmt::Display for IRCError {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        write!(f, "{} {}", self.error_code, self.raw_args.as_ref().unwrap())
+        match self.arguments {
+            Some(ref args) => write!(f, "{} {}", self.error_code, args.connect(" ")),
+            None => write!(f, "{} (ignored)", self.error_code),
+        }
     }
 }
 
@@ -54,3 +94,9 @@ impl error::Error for IRCError {
         None
     }
 }
+
+impl<'a> From<Reply<'a>> for IRCError {
+    fn from(reply: Reply<'a>) -> IRCError {
+        IRCError::from_reply(reply)
+    }
+}
@@ -1,64 +1,174 @@
-use std::fmt;
+use std::{fmt, error, io};
 
 pub mod error;
-mod numerics;
+pub mod owned;
+pub mod tags;
+pub mod prefix;
+pub mod command;
+pub mod reply;
+pub mod numerics;
 
-pub struct Message<'a> {
-    pub prefix: Option<&'a str>,
-    pub command: &'a str,
-    pub arguments: Vec<&'a str>,
+pub use self::owned::OwnedMessage;
+pub use self::tags::Tag;
+pub use self::prefix::Prefix;
+pub use self::command::Command;
+pub use self::reply::Reply;
+
+/// RFC 1459 allows at most 15 parameters in a message.
+pub const MAX_PARAMETERS: usize = 15;
+
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    /// There's nothing in the line.
+    EmptyLine,
+    /// The line has a prefix, but no command following it.
+    MissingCommand,
+    /// The line has more than `MAX_PARAMETERS` parameters.
+    TooManyParameters,
+    /// The line contains a NUL, CR or LF.
+    IllegalCharacter,
+    /// The command is neither a word nor a three digit numeric.
+    InvalidCommand(String),
+    /// The tags section is malformed.
+    InvalidTags,
+    /// The tags section is longer than `tags::MAX_CLIENT_TAGS_LENGTH`.
+    TagsTooLong,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            ParseError::EmptyLine => write!(f, "empty line"),
+            ParseError::MissingCommand => write!(f, "prefix without a command"),
+            ParseError::TooManyParameters =>
+                write!(f, "more than {} parameters", MAX_PARAMETERS),
+            ParseError::IllegalCharacter => write!(f, "NUL, CR or LF in line"),
+            ParseError::InvalidCommand(ref command) => write!(f, "invalid command '{}'", command),
+            ParseError::InvalidTags => write!(f, "malformed tags"),
+            ParseError::TagsTooLong => write!(f, "tags longer than {} bytes", tags::MAX_CLIENT_TAGS_LENGTH),
+        }
+    }
 }
 
-// pub type MessageProcessor = fn(ctx: &mut Context, message: &Message) -> Result<(), error::IRCError>;
+impl error::Error for ParseError {
+    fn description(&self) -> &str {
+        "parsing the message"
+    }
+
+    fn cause(&self) -> Option<&error::Error> {
+        None
+    }
+}
 
-// pub fn resolve(line: &str) -> Result<(Message, MessageProcessor), error::IRCError> {
-//     let raw_message = if let Some(msg) = Message::new(line) {
-//         msg
-//     } else {
-//         return Err(error::IRCError::ignore())  // TODO: unparseable line
-//     };
+fn is_valid_command(command: &str) -> bool {
+    let bytes = command.as_bytes();
+    if bytes.len() == 3 && bytes.iter().all(|b| *b >= b'0' && *b <= b'9') {
+        return true;
+    }
+    bytes.len() > 0 && bytes.iter().all(|b| (*b >= b'a' && *b <= b'z') || (*b >= b'A' && *b <= b'Z'))
+}
 
-//     match raw_message.command.to_uppercase().as_str() {
-//         // "PASS" => Ok((raw_message, password::process_pass)),
-//         "NICK" => Ok((raw_message, nick::process_nick)),
-//         // "USER" => Ok((raw_message, user::process_user)),
-//         _ => Err(error::IRCError::ignore()) // TODO: (format!("unknown command '{}'", other).as_str()))
-//     }
-// }
+/// Whether the argument can only be sent as the trailing one.
+fn needs_trailing(argument: &str) -> bool {
+    argument.len() == 0 || argument.starts_with(":") || argument.contains(' ')
+}
 
-// pub fn process(server: &Server, user: &UserClient, line: &str) -> Result<(), error::IRCError> {
-//     let (raw_message, handler) = try!(resolve(line));
-//     handler(server, user, &raw_message)
-// }
+#[derive(Debug)]
+pub struct Message<'a> {
+    pub tags: Vec<Tag>,
+    pub prefix: Option<Prefix<'a>>,
+    pub command: &'a str,
+    pub arguments: Vec<&'a str>,
+}
 
 impl<'a> Message<'a> {
-    pub fn build(prefix: Option<&'a str>, command: &'static str, arguments: Vec<&'a str>) -> Message<'a> {
+    pub fn build(prefix: Option<Prefix<'a>>, command: &'a str, arguments: Vec<&'a str>) -> Message<'a> {
         Message {
+            tags: vec![],
             prefix: prefix,
             command: command,
             arguments: arguments
         }
     }
 
-    pub fn new(line: &'a str) -> Option<Message> {
+    /// Value of the tag with the given key. If the key is repeated, the last
+    /// occurrence wins.
+    pub fn tag(&self, key: &str) -> Option<&Tag> {
+        self.tags.iter().rev().find(|tag| tag.key == key)
+    }
+
+    /// Whether the message has a wire form which parses back into the very
+    /// same message.
+    pub fn can_serialize(&self) -> bool {
+        let illegal = |s: &str| s.contains(|c: char| c == '\0' || c == '\r' || c == '\n');
+
+        if !is_valid_command(self.command) || self.arguments.len() > MAX_PARAMETERS {
+            return false;
+        }
+        if !self.tags.iter().all(|tag| Tag::is_valid_key(tag.key.as_str())) {
+            return false;
+        }
+        if let Some(ref prefix) = self.prefix {
+            if !prefix.is_valid() {
+                return false;
+            }
+        }
+
+        let middle = if self.arguments.len() > 0 { self.arguments.len() - 1 } else { 0 };
+        self.arguments.iter().all(|arg| !illegal(arg)) &&
+            self.arguments[..middle].iter().all(|arg| !needs_trailing(arg))
+    }
+
+    /// Writes the message along with the terminating CR-LF.
+    pub fn write_to(&self, w: &mut io::Write) -> io::Result<()> {
+        if !self.can_serialize() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                "message has no valid wire form"));
+        }
+        write!(w, "{}\r\n", self)
+    }
+
+    pub fn new(line: &'a str) -> Result<Message, ParseError> {
         let mut remains = line;
 
         let command: &'a str;
         let mut arguments = vec![];
 
+        if line.contains(|c: char| c == '\0' || c == '\r' || c == '\n') {
+            return Err(ParseError::IllegalCharacter);
+        }
+
+        let tags = if remains.starts_with("@") {
+            let tags_end = if let Some(tags_end) = remains.find(' ') {
+                tags_end
+            } else {
+                return Err(ParseError::MissingCommand);
+            };
+            if tags_end + 1 > tags::MAX_CLIENT_TAGS_LENGTH {
+                return Err(ParseError::TagsTooLong);
+            }
+            let tags = try!(tags::parse_tags(&remains[1..tags_end]));
+            remains = &remains[tags_end+1..];
+            tags
+        } else {
+            vec![]
+        };
+
         let first_char = if let Some(first) = remains.chars().next() {
             first
+        } else if tags.is_empty() {
+            return Err(ParseError::EmptyLine)
         } else {
-            return None
+            return Err(ParseError::MissingCommand)
         };
 
         let prefix = if first_char == ':' {
             if let Some(command_start) = remains.find(' ') {
                 let prefix = &remains[1..command_start];
                 remains = &remains[command_start+1..];
-                Some(prefix)
+                Some(Prefix::parse(prefix))
             } else {
-                return None;
+                return Err(ParseError::MissingCommand);
             }
         } else {
             None
@@ -91,7 +201,23 @@ impl<'a> Message<'a> {
         } else {
             command = remains;
         }
-        Some(Message {
+
+        if command.len() == 0 {
+            return Err(if prefix.is_some() || !tags.is_empty() {
+                ParseError::MissingCommand
+            } else {
+                ParseError::EmptyLine
+            });
+        }
+        if !is_valid_command(command) {
+            return Err(ParseError::InvalidCommand(command.to_string()));
+        }
+        if arguments.len() > MAX_PARAMETERS {
+            return Err(ParseError::TooManyParameters);
+        }
+
+        Ok(Message {
+            tags: tags,
             prefix: prefix,
             command: command,
             arguments: arguments,
@@ -99,19 +225,30 @@ impl<'a> Message<'a> {
     }
 }
 
-impl<'a> fmt::Debug for Message<'a> {
+/// Wire form of the message, without the CR-LF. The trailing form is only
+/// used for the last argument, and only when necessary. Messages which can
+/// not be serialized are still formatted, use `write_to` for the wire.
+impl<'a> fmt::Display for Message<'a> {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        if let Some(prefix) = self.prefix {
+        for (index, tag) in self.tags.iter().enumerate() {
+            try!(fmt.write_str(if index == 0 { "@" } else { ";" }));
+            try!(fmt.write_fmt(format_args!("{}", tag)));
+        }
+        if !self.tags.is_empty() {
+            try!(fmt.write_str(" "));
+        }
+
+        if let Some(ref prefix) = self.prefix {
             try!(fmt.write_fmt(format_args!(":{} ", prefix)));
         }
         try!(fmt.write_str(self.command));
 
-        for arg in self.arguments.iter() {
-            if arg.find(' ') == None {
-                try!(fmt.write_fmt(format_args!(" {}", arg)));
-            } else {
+        let last = self.arguments.len();
+        for (index, arg) in self.arguments.iter().enumerate() {
+            if index + 1 == last && needs_trailing(arg) {
                 try!(fmt.write_fmt(format_args!(" :{}", arg)));
-                return Ok(())
+            } else {
+                try!(fmt.write_fmt(format_args!(" {}", arg)));
             }
         }
 
@@ -121,13 +258,14 @@ impl<'a> fmt::Debug for Message<'a> {
 
 #[cfg(test)]
 mod test {
-    pub use super::Message;
+    pub use std::iter::repeat;
+    pub use super::{Message, ParseError, Tag, Prefix};
     pub use hamcrest::{assert_that, is, not, none, equal_to};
 
     describe! parse {
         it "parses a simple line successfully" {
             let m = Message::new("REHASH");
-            assert_that(m, is(not(none::<Message>())));
+            assert_that(m.is_ok(), is(equal_to(true)));
         }
 
         it "resolves command for a simple line" {
@@ -137,7 +275,7 @@ mod test {
 
         it "parses a line with arguments successfully" {
             let m = Message::new("NICK hello");
-            assert_that(m, is(not(none::<Message>())));
+            assert_that(m.is_ok(), is(equal_to(true)));
         }
 
         it "resolves command for a line with arguments" {
@@ -162,12 +300,212 @@ mod test {
 
         it "parses a servername prefix" {
             let m = Message::new(":my.local.server PONG");
-            assert_that(m.unwrap().prefix, is(equal_to(Some("my.local.server"))));
+            assert_that(m.unwrap().prefix, is(equal_to(Some(Prefix::Server("my.local.server")))));
+        }
+
+        it "parses a user prefix" {
+            let m = Message::new(":nick!user@host PRIVMSG test hi");
+            assert_that(m.unwrap().prefix, is(equal_to(Some(Prefix::User {
+                nick: "nick", user: Some("user"), host: Some("host") }))));
+        }
+
+        it "parses a numeric command" {
+            let m = Message::new(":my.local.server 001 test :Welcome");
+            assert_that(m.unwrap().command, is(equal_to("001")));
+        }
+
+        it "accepts up to 15 parameters" {
+            let m = Message::new("FOO 1 2 3 4 5 6 7 8 9 10 11 12 13 14 :15 16");
+            assert_that(m.unwrap().arguments.len(), is(equal_to(15)));
+        }
+
+        it "fails on an empty line" {
+            let m = Message::new("");
+            assert_that(m.err(), is(equal_to(Some(ParseError::EmptyLine))));
+        }
+
+        it "fails on a line of spaces" {
+            let m = Message::new("   ");
+            assert_that(m.err(), is(equal_to(Some(ParseError::EmptyLine))));
         }
 
-        it "returns None if the command is malformed" {
+        it "fails if the prefix has no command following it" {
             let m = Message::new(":no_command");
-            assert_that(m, is(none::<Message>()));
+            assert_that(m.err(), is(equal_to(Some(ParseError::MissingCommand))));
+
+            let m = Message::new(":no_command ");
+            assert_that(m.err(), is(equal_to(Some(ParseError::MissingCommand))));
+        }
+
+        it "fails on more than 15 parameters" {
+            let m = Message::new("FOO 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16");
+            assert_that(m.err(), is(equal_to(Some(ParseError::TooManyParameters))));
+        }
+
+        it "fails on embedded NUL, CR or LF" {
+            for line in ["NICK a\0b", "NICK a\rb", "NICK a\nb"].iter() {
+                let m = Message::new(line);
+                assert_that(m.err(), is(equal_to(Some(ParseError::IllegalCharacter))));
+            }
+        }
+
+        it "fails on an invalid command token" {
+            let m = Message::new("N1CK test");
+            assert_that(m.err(), is(equal_to(Some(ParseError::InvalidCommand("N1CK".to_string())))));
+
+            let m = Message::new("0001 test");
+            assert_that(m.err(), is(equal_to(Some(ParseError::InvalidCommand("0001".to_string())))));
+        }
+    }
+
+    describe! tags {
+        it "parses tags" {
+            let m = Message::new("@aaa=bbb;ccc;+example.com/ddd=eee :nick!ident@host.com PRIVMSG me :Hello").unwrap();
+
+            assert_that(m.tags, is(equal_to(vec![
+                Tag::new("aaa", Some("bbb")),
+                Tag::new("ccc", None),
+                Tag::new("+example.com/ddd", Some("eee"))])));
+            assert_that(m.prefix, is(equal_to(Some(Prefix::parse("nick!ident@host.com")))));
+            assert_that(m.command, is(equal_to("PRIVMSG")));
+            assert_that(m.arguments, is(equal_to(vec!["me", "Hello"])));
+        }
+
+        it "has no tags on an untagged line" {
+            let m = Message::new("PING x").unwrap();
+            assert_that(m.tags.len(), is(equal_to(0)));
+        }
+
+        it "finds the last occurrence of a tag" {
+            let m = Message::new("@a=1;b;a=2 PING x").unwrap();
+
+            assert_that(m.tag("a").and_then(|t| t.value.clone()), is(equal_to(Some("2".to_string()))));
+            assert_that(m.tag("b").is_some(), is(equal_to(true)));
+            assert_that(m.tag("c").is_some(), is(equal_to(false)));
+        }
+
+        it "fails on tags without a command" {
+            assert_that(Message::new("@a=b").err(), is(equal_to(Some(ParseError::MissingCommand))));
+            assert_that(Message::new("@a=b ").err(), is(equal_to(Some(ParseError::MissingCommand))));
+        }
+
+        it "fails on malformed tags" {
+            assert_that(Message::new("@ PING x").err(), is(equal_to(Some(ParseError::InvalidTags))));
+        }
+
+        it "limits the size of the tags" {
+            let value: String = repeat('a').take(4090).collect();
+            let fits = format!("@a={} PING x", value);
+            let too_long = format!("@ab={} PING x", value);
+
+            assert_that(Message::new(fits.as_str()).is_ok(), is(equal_to(true)));
+            assert_that(Message::new(too_long.as_str()).err(), is(equal_to(Some(ParseError::TagsTooLong))));
+        }
+
+        it "writes tags before the prefix" {
+            let mut m = Message::build(Some(Prefix::Server("srv")), "PRIVMSG", vec!["test", "hi"]);
+            m.tags = vec![Tag::new("time", Some("2015-06-01T12:00:00.000Z")), Tag::new("+x", Some("a;b"))];
+
+            assert_that(m.to_string(),
+                is(equal_to("@time=2015-06-01T12:00:00.000Z;+x=a\\:b :srv PRIVMSG test hi".to_string())));
+        }
+
+        it "round-trips tags" {
+            let line = "@+example.com/typing=active;msgid=a\\sb :srv TAGMSG #chan";
+            assert_that(Message::new(line).unwrap().to_string(), is(equal_to(line.to_string())));
+        }
+    }
+
+    describe! serialize {
+        it "builds from a dynamic command" {
+            let command = format!("{:03}", 5);
+            let m = Message::build(None, command.as_str(), vec!["test"]);
+            assert_that(m.to_string(), is(equal_to("005 test".to_string())));
+        }
+
+        it "writes a simple command" {
+            let m = Message::build(None, "REHASH", vec![]);
+            assert_that(m.to_string(), is(equal_to("REHASH".to_string())));
+        }
+
+        it "writes the prefix" {
+            let m = Message::build(Some(Prefix::Server("my.local.server")), "PONG", vec!["my.local.server"]);
+            assert_that(m.to_string(), is(equal_to(":my.local.server PONG my.local.server".to_string())));
+        }
+
+        it "uses the trailing form for a last argument with spaces" {
+            let m = Message::build(None, "USER", vec!["guest", "0", "*", "Ronnie Reagan"]);
+            assert_that(m.to_string(), is(equal_to("USER guest 0 * :Ronnie Reagan".to_string())));
+        }
+
+        it "uses the trailing form for an empty last argument" {
+            let m = Message::build(None, "PRIVMSG", vec!["test", ""]);
+            assert_that(m.to_string(), is(equal_to("PRIVMSG test :".to_string())));
+        }
+
+        it "uses the trailing form for a last argument starting with a colon" {
+            let m = Message::build(None, "PRIVMSG", vec!["test", ":)"]);
+            assert_that(m.to_string(), is(equal_to("PRIVMSG test ::)".to_string())));
+        }
+
+        it "does not use the trailing form when not necessary" {
+            let m = Message::build(None, "PRIVMSG", vec!["test", "hello"]);
+            assert_that(m.to_string(), is(equal_to("PRIVMSG test hello".to_string())));
+        }
+
+        it "refuses spaces, colons and emptiness in middle arguments" {
+            for args in vec![vec!["a b", "c"], vec![":a", "c"], vec!["", "c"]].into_iter() {
+                let m = Message::build(None, "PRIVMSG", args);
+                assert_that(m.can_serialize(), is(equal_to(false)));
+            }
+        }
+
+        it "refuses an invalid prefix" {
+            let m = Message::build(Some(Prefix::Server("my server")), "PING", vec!["x"]);
+            assert_that(m.can_serialize(), is(equal_to(false)));
+        }
+
+        it "refuses line breaks" {
+            let m = Message::build(None, "PRIVMSG", vec!["test", "hello\r\nQUIT"]);
+            assert_that(m.can_serialize(), is(equal_to(false)));
+        }
+
+        it "terminates lines with CR-LF" {
+            let mut buf: Vec<u8> = Vec::new();
+            Message::build(None, "PRIVMSG", vec!["test", "hello"]).write_to(&mut buf).ok().unwrap();
+            assert_that(buf, is(equal_to(b"PRIVMSG test hello\r\n".to_vec())));
+        }
+
+        it "fails to write a message without wire form" {
+            let mut buf: Vec<u8> = Vec::new();
+            let result = Message::build(None, "PRIVMSG", vec!["a b", "c"]).write_to(&mut buf);
+            assert_that(result.is_err(), is(equal_to(true)));
+            assert_that(buf.len(), is(equal_to(0)));
+        }
+
+        it "still formats a message without wire form" {
+            let m = Message::build(None, "PRIVMSG", vec!["a b", "c"]);
+            assert_that(m.to_string(), is(equal_to("PRIVMSG a b c".to_string())));
+        }
+
+        it "round-trips through the parser" {
+            let lines = [
+                ":nick!user@host PRIVMSG #chan ::-) hello",
+                "PRIVMSG test :",
+                "NICK test",
+                ":srv 001 test :Welcome to the network",
+                "MODE #chan +o test"];
+
+            for line in lines.iter() {
+                let m = Message::new(line).unwrap();
+                let written = m.to_string();
+                let m2 = Message::new(written.as_str()).unwrap();
+
+                assert_that(written.as_str(), is(equal_to(*line)));
+                assert_that(m2.prefix, is(equal_to(m.prefix)));
+                assert_that(m2.command, is(equal_to(m.command)));
+                assert_that(m2.arguments, is(equal_to(m.arguments)));
+            }
         }
     }
 }
@@ -1 +1,68 @@
 pub mod rfc1459;
+pub mod rfc2812;
+pub mod modern;
+
+/// Symbolic description of a numeric reply.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Numeric {
+    pub code: u32,
+    pub name: &'static str,
+    /// Arguments following the target, as given by the specification.
+    pub format: &'static str,
+}
+
+/// Looks up the numeric with the given code. Where the catalogues disagree
+/// the meaning in current use wins, e.g. 005 is RPL_ISUPPORT rather than
+/// RFC 2812's RPL_BOUNCE.
+pub fn lookup(code: u32) -> Option<Numeric> {
+    let tables = [modern::NUMERICS, rfc2812::NUMERICS, rfc1459::NUMERICS];
+
+    for table in tables.iter() {
+        if let Some(numeric) = table.iter().find(|n| n.code == code) {
+            return Some(*numeric);
+        }
+    }
+    None
+}
+
+/// Symbolic name of the numeric, or its zero padded code if unknown.
+pub fn name_of(code: u32) -> String {
+    match lookup(code) {
+        Some(numeric) => numeric.name.to_string(),
+        None => format!("{:03}", code),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    pub use super::{lookup, name_of, Numeric};
+    pub use hamcrest::{assert_that, is, not, none, equal_to};
+
+    describe! numerics {
+        it "finds RFC 1459 replies" {
+            assert_that(lookup(401), is(equal_to(Some(Numeric {
+                code: 401,
+                name: "ERR_NOSUCHNICK",
+                format: "<nickname> :No such nick/channel",
+            }))));
+        }
+
+        it "finds RFC 2812 replies" {
+            assert_that(lookup(1).unwrap().name, is(equal_to("RPL_WELCOME")));
+        }
+
+        it "prefers the modern meaning" {
+            assert_that(lookup(5).unwrap().name, is(equal_to("RPL_ISUPPORT")));
+            assert_that(lookup(904).unwrap().name, is(equal_to("ERR_SASLFAIL")));
+        }
+
+        it "does not know made up codes" {
+            assert_that(lookup(999), is(none()));
+        }
+
+        it "names numerics" {
+            assert_that(name_of(417), is(equal_to("ERR_INPUTTOOLONG".to_string())));
+            assert_that(name_of(999), is(equal_to("999".to_string())));
+        }
+    }
+}
@@ -0,0 +1,79 @@
+//! Replies which are not part of any RFC but are in common use among
+//! current servers and clients, including the ones of the IRCv3
+//! extensions.
+
+use super::Numeric;
+
+/// Advertises the features supported by the server, sent along with
+/// the welcome burst. This replaces the RPL_BOUNCE of RFC 2812.
+pub const RPL_ISUPPORT: u32 = 5;  // "<token>[ <token>] :are supported by this server"
+
+/// Unique ID assigned to the client on networks using them.
+pub const RPL_YOURID: u32 = 42;  // "<id> :your unique ID"
+
+/// User counts sent with LUSERS.
+pub const RPL_LOCALUSERS: u32 = 265;  // "[<u> <m>] :Current local users <u>, max <m>"
+pub const RPL_GLOBALUSERS: u32 = 266;  // "[<u> <m>] :Current global users <u>, max <m>"
+
+/// Sent when the host shown to other users changes, e.g. when a cloak
+/// gets applied.
+pub const RPL_VISIBLEHOST: u32 = 396;  // "<hostname> :is now your displayed host"
+
+/// Reports a CAP subcommand the server does not know.
+pub const ERR_INVALIDCAPCMD: u32 = 410;  // "<subcommand> :Invalid CAP command"
+
+/// Reports a line which went over the protocol limit.
+pub const ERR_INPUTTOOLONG: u32 = 417;  // ":Input line was too long"
+
+/// STARTTLS replies.
+pub const RPL_STARTTLS: u32 = 670;  // ":STARTTLS successful, proceed with TLS handshake"
+pub const ERR_STARTTLS: u32 = 691;  // ":STARTTLS failed"
+
+/// Sent with the WHOIS replies of a user connected through TLS.
+pub const RPL_WHOISSECURE: u32 = 671;  // "<nick> :is using a secure connection"
+
+/// MONITOR replies.
+pub const RPL_MONONLINE: u32 = 730;  // ":<target>[,<target2>]*"
+pub const RPL_MONOFFLINE: u32 = 731;  // ":<target>[,<target2>]*"
+pub const RPL_MONLIST: u32 = 732;  // ":<target>[,<target2>]*"
+pub const RPL_ENDOFMONLIST: u32 = 733;  // ":End of MONITOR list"
+pub const ERR_MONLISTFULL: u32 = 734;  // "<limit> <targets> :Monitor list is full."
+
+/// SASL replies.
+pub const RPL_LOGGEDIN: u32 = 900;  // "<nick>!<ident>@<host> <account> :You are now logged in as <username>"
+pub const RPL_LOGGEDOUT: u32 = 901;  // "<nick>!<ident>@<host> :You are now logged out"
+pub const ERR_NICKLOCKED: u32 = 902;  // ":You must use a nick assigned to you"
+pub const RPL_SASLSUCCESS: u32 = 903;  // ":SASL authentication successful"
+pub const ERR_SASLFAIL: u32 = 904;  // ":SASL authentication failed"
+pub const ERR_SASLTOOLONG: u32 = 905;  // ":SASL message too long"
+pub const ERR_SASLABORTED: u32 = 906;  // ":SASL authentication aborted"
+pub const ERR_SASLALREADY: u32 = 907;  // ":You have already authenticated using SASL"
+pub const RPL_SASLMECHS: u32 = 908;  // "<mechanisms> :are available SASL mechanisms"
+
+/// All the replies above, for `numerics::lookup`.
+pub static NUMERICS: &'static [Numeric] = &[
+    Numeric { code: RPL_ISUPPORT, name: "RPL_ISUPPORT", format: "<token>[ <token>] :are supported by this server" },
+    Numeric { code: RPL_YOURID, name: "RPL_YOURID", format: "<id> :your unique ID" },
+    Numeric { code: RPL_LOCALUSERS, name: "RPL_LOCALUSERS", format: "[<u> <m>] :Current local users <u>, max <m>" },
+    Numeric { code: RPL_GLOBALUSERS, name: "RPL_GLOBALUSERS", format: "[<u> <m>] :Current global users <u>, max <m>" },
+    Numeric { code: RPL_VISIBLEHOST, name: "RPL_VISIBLEHOST", format: "<hostname> :is now your displayed host" },
+    Numeric { code: ERR_INVALIDCAPCMD, name: "ERR_INVALIDCAPCMD", format: "<subcommand> :Invalid CAP command" },
+    Numeric { code: ERR_INPUTTOOLONG, name: "ERR_INPUTTOOLONG", format: ":Input line was too long" },
+    Numeric { code: RPL_STARTTLS, name: "RPL_STARTTLS", format: ":STARTTLS successful, proceed with TLS handshake" },
+    Numeric { code: ERR_STARTTLS, name: "ERR_STARTTLS", format: ":STARTTLS failed" },
+    Numeric { code: RPL_WHOISSECURE, name: "RPL_WHOISSECURE", format: "<nick> :is using a secure connection" },
+    Numeric { code: RPL_MONONLINE, name: "RPL_MONONLINE", format: ":<target>[,<target2>]*" },
+    Numeric { code: RPL_MONOFFLINE, name: "RPL_MONOFFLINE", format: ":<target>[,<target2>]*" },
+    Numeric { code: RPL_MONLIST, name: "RPL_MONLIST", format: ":<target>[,<target2>]*" },
+    Numeric { code: RPL_ENDOFMONLIST, name: "RPL_ENDOFMONLIST", format: ":End of MONITOR list" },
+    Numeric { code: ERR_MONLISTFULL, name: "ERR_MONLISTFULL", format: "<limit> <targets> :Monitor list is full." },
+    Numeric { code: RPL_LOGGEDIN, name: "RPL_LOGGEDIN", format: "<nick>!<ident>@<host> <account> :You are now logged in as <username>" },
+    Numeric { code: RPL_LOGGEDOUT, name: "RPL_LOGGEDOUT", format: "<nick>!<ident>@<host> :You are now logged out" },
+    Numeric { code: ERR_NICKLOCKED, name: "ERR_NICKLOCKED", format: ":You must use a nick assigned to you" },
+    Numeric { code: RPL_SASLSUCCESS, name: "RPL_SASLSUCCESS", format: ":SASL authentication successful" },
+    Numeric { code: ERR_SASLFAIL, name: "ERR_SASLFAIL", format: ":SASL authentication failed" },
+    Numeric { code: ERR_SASLTOOLONG, name: "ERR_SASLTOOLONG", format: ":SASL message too long" },
+    Numeric { code: ERR_SASLABORTED, name: "ERR_SASLABORTED", format: ":SASL authentication aborted" },
+    Numeric { code: ERR_SASLALREADY, name: "ERR_SASLALREADY", format: ":You have already authenticated using SASL" },
+    Numeric { code: RPL_SASLMECHS, name: "RPL_SASLMECHS", format: "<mechanisms> :are available SASL mechanisms" },
+];
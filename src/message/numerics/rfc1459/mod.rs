@@ -1,4 +1,128 @@
 pub mod error_replies;
 pub mod command_replies;
 
+use super::Numeric;
+
 // ^(\d+)\s+(\w+)$\n\s+("[^"]+") --> pub const $2: u32 = $1;  // $3
+
+/// All the replies of both modules, for `numerics::lookup`.
+pub static NUMERICS: &'static [Numeric] = &[
+    Numeric { code: command_replies::RPL_NONE, name: "RPL_NONE", format: "" },
+    Numeric { code: command_replies::RPL_USERHOST, name: "RPL_USERHOST", format: ":[<reply>{<space><reply>}]" },
+    Numeric { code: command_replies::RPL_ISON, name: "RPL_ISON", format: ":[<nick> {<space><nick>}]" },
+    Numeric { code: command_replies::RPL_AWAY, name: "RPL_AWAY", format: "<nick> :<away message>" },
+    Numeric { code: command_replies::RPL_UNAWAY, name: "RPL_UNAWAY", format: ":You are no longer marked as being away" },
+    Numeric { code: command_replies::RPL_NOWAWAY, name: "RPL_NOWAWAY", format: ":You have been marked as being away" },
+    Numeric { code: command_replies::RPL_WHOISUSER, name: "RPL_WHOISUSER", format: "<nick> <user> <host> * :<real name>" },
+    Numeric { code: command_replies::RPL_WHOISSERVER, name: "RPL_WHOISSERVER", format: "<nick> <server> :<server info>" },
+    Numeric { code: command_replies::RPL_WHOISOPERATOR, name: "RPL_WHOISOPERATOR", format: "<nick> :is an IRC operator" },
+    Numeric { code: command_replies::RPL_WHOISIDLE, name: "RPL_WHOISIDLE", format: "<nick> <integer> :seconds idle" },
+    Numeric { code: command_replies::RPL_ENDOFWHOIS, name: "RPL_ENDOFWHOIS", format: "<nick> :End of /WHOIS list" },
+    Numeric { code: command_replies::RPL_WHOISCHANNELS, name: "RPL_WHOISCHANNELS", format: "<nick> :{[@|+]<channel><space>}" },
+    Numeric { code: command_replies::RPL_WHOWASUSER, name: "RPL_WHOWASUSER", format: "<nick> <user> <host> * :<real name>" },
+    Numeric { code: command_replies::RPL_ENDOFWHOWAS, name: "RPL_ENDOFWHOWAS", format: "<nick> :End of WHOWAS" },
+    Numeric { code: command_replies::RPL_LISTSTART, name: "RPL_LISTSTART", format: "Channel :Users  Name" },
+    Numeric { code: command_replies::RPL_LIST, name: "RPL_LIST", format: "<channel> <# visible> :<topic>" },
+    Numeric { code: command_replies::RPL_LISTEND, name: "RPL_LISTEND", format: ":End of /LIST" },
+    Numeric { code: command_replies::RPL_CHANNELMODEIS, name: "RPL_CHANNELMODEIS", format: "<channel> <mode> <mode params>" },
+    Numeric { code: command_replies::RPL_NOTOPIC, name: "RPL_NOTOPIC", format: "<channel> :No topic is set" },
+    Numeric { code: command_replies::RPL_TOPIC, name: "RPL_TOPIC", format: "<channel> :<topic>" },
+    Numeric { code: command_replies::RPL_INVITING, name: "RPL_INVITING", format: "<channel> <nick>" },
+    Numeric { code: command_replies::RPL_SUMMONING, name: "RPL_SUMMONING", format: "<user> :Summoning user to IRC" },
+    Numeric { code: command_replies::RPL_VERSION, name: "RPL_VERSION", format: "<version>.<debuglevel> <server> :<comments>" },
+    Numeric { code: command_replies::RPL_WHOREPLY, name: "RPL_WHOREPLY", format: "<channel> <user> <host> <server> <nick> <H|G>[*][@|+] :<hopcount> <real name>" },
+    Numeric { code: command_replies::RPL_ENDOFWHO, name: "RPL_ENDOFWHO", format: "<name> :End of /WHO list" },
+    Numeric { code: command_replies::RPL_NAMREPLY, name: "RPL_NAMREPLY", format: "<channel> :[[@|+]<nick> [[@|+]<nick> [...]]]" },
+    Numeric { code: command_replies::RPL_ENDOFNAMES, name: "RPL_ENDOFNAMES", format: "<channel> :End of /NAMES list" },
+    Numeric { code: command_replies::RPL_LINKS, name: "RPL_LINKS", format: "<mask> <server> :<hopcount> <server info>" },
+    Numeric { code: command_replies::RPL_ENDOFLINKS, name: "RPL_ENDOFLINKS", format: "<mask> :End of /LINKS list" },
+    Numeric { code: command_replies::RPL_BANLIST, name: "RPL_BANLIST", format: "<channel> <banid>" },
+    Numeric { code: command_replies::RPL_ENDOFBANLIST, name: "RPL_ENDOFBANLIST", format: "<channel> :End of channel ban list" },
+    Numeric { code: command_replies::RPL_INFO, name: "RPL_INFO", format: ":<string>" },
+    Numeric { code: command_replies::RPL_ENDOFINFO, name: "RPL_ENDOFINFO", format: ":End of /INFO list" },
+    Numeric { code: command_replies::RPL_MOTDSTART, name: "RPL_MOTDSTART", format: ":- <server> Message of the day - " },
+    Numeric { code: command_replies::RPL_MOTD, name: "RPL_MOTD", format: ":- <text>" },
+    Numeric { code: command_replies::RPL_ENDOFMOTD, name: "RPL_ENDOFMOTD", format: ":End of /MOTD command" },
+    Numeric { code: command_replies::RPL_YOUREOPER, name: "RPL_YOUREOPER", format: ":You are now an IRC operator" },
+    Numeric { code: command_replies::RPL_REHASHING, name: "RPL_REHASHING", format: "<config file> :Rehashing" },
+    Numeric { code: command_replies::RPL_TIME, name: "RPL_TIME", format: "<server> :<string showing server's local time>" },
+    Numeric { code: command_replies::RPL_USERSSTART, name: "RPL_USERSSTART", format: ":UserID   Terminal  Host" },
+    Numeric { code: command_replies::RPL_USERS, name: "RPL_USERS", format: ":%-8s %-9s %-8s" },
+    Numeric { code: command_replies::RPL_ENDOFUSERS, name: "RPL_ENDOFUSERS", format: ":End of users" },
+    Numeric { code: command_replies::RPL_NOUSERS, name: "RPL_NOUSERS", format: ":Nobody logged in" },
+    Numeric { code: command_replies::RPL_TRACELINK, name: "RPL_TRACELINK", format: "Link <version & debug level> <destination> <next server>" },
+    Numeric { code: command_replies::RPL_TRACECONNECTING, name: "RPL_TRACECONNECTING", format: "Try. <class> <server>" },
+    Numeric { code: command_replies::RPL_TRACEHANDSHAKE, name: "RPL_TRACEHANDSHAKE", format: "H.S. <class> <server>" },
+    Numeric { code: command_replies::RPL_TRACEUNKNOWN, name: "RPL_TRACEUNKNOWN", format: "???? <class> [<client IP address in dot form>]" },
+    Numeric { code: command_replies::RPL_TRACEOPERATOR, name: "RPL_TRACEOPERATOR", format: "Oper <class> <nick>" },
+    Numeric { code: command_replies::RPL_TRACEUSER, name: "RPL_TRACEUSER", format: "User <class> <nick>" },
+    Numeric { code: command_replies::RPL_TRACESERVER, name: "RPL_TRACESERVER", format: "Serv <class> <int>S <int>C <server> <nick!user|*!*>@<host|server>" },
+    Numeric { code: command_replies::RPL_TRACENEWTYPE, name: "RPL_TRACENEWTYPE", format: "<newtype> 0 <client name>" },
+    Numeric { code: command_replies::RPL_TRACELOG, name: "RPL_TRACELOG", format: "File <logfile> <debug level>" },
+    Numeric { code: command_replies::RPL_STATSLINKINFO, name: "RPL_STATSLINKINFO", format: "<linkname> <sendq> <sent messages> <sent bytes> <received messages> <received bytes> <time open>" },
+    Numeric { code: command_replies::RPL_STATSCOMMANDS, name: "RPL_STATSCOMMANDS", format: "<command> <count>" },
+    Numeric { code: command_replies::RPL_STATSCLINE, name: "RPL_STATSCLINE", format: "C <host> * <name> <port> <class>" },
+    Numeric { code: command_replies::RPL_STATSNLINE, name: "RPL_STATSNLINE", format: "N <host> * <name> <port> <class>" },
+    Numeric { code: command_replies::RPL_STATSILINE, name: "RPL_STATSILINE", format: "I <host> * <host> <port> <class>" },
+    Numeric { code: command_replies::RPL_STATSKLINE, name: "RPL_STATSKLINE", format: "K <host> * <username> <port> <class>" },
+    Numeric { code: command_replies::RPL_STATSYLINE, name: "RPL_STATSYLINE", format: "Y <class> <ping frequency> <connect frequency> <max sendq>" },
+    Numeric { code: command_replies::RPL_ENDOFSTATS, name: "RPL_ENDOFSTATS", format: "<stats letter> :End of /STATS report" },
+    Numeric { code: command_replies::RPL_STATSLLINE, name: "RPL_STATSLLINE", format: "L <hostmask> * <servername> <maxdepth>" },
+    Numeric { code: command_replies::RPL_STATSUPTIME, name: "RPL_STATSUPTIME", format: ":Server Up %d days %d:%02d:%02d" },
+    Numeric { code: command_replies::RPL_STATSOLINE, name: "RPL_STATSOLINE", format: "O <hostmask> * <name>" },
+    Numeric { code: command_replies::RPL_STATSHLINE, name: "RPL_STATSHLINE", format: "H <hostmask> * <servername>" },
+    Numeric { code: command_replies::RPL_UMODEIS, name: "RPL_UMODEIS", format: "<user mode string>" },
+    Numeric { code: command_replies::RPL_LUSERCLIENT, name: "RPL_LUSERCLIENT", format: ":There are <integer> users and <integer> invisible on <integer> servers" },
+    Numeric { code: command_replies::RPL_LUSEROP, name: "RPL_LUSEROP", format: "<integer> :operator(s) online" },
+    Numeric { code: command_replies::RPL_LUSERUNKNOWN, name: "RPL_LUSERUNKNOWN", format: "<integer> :unknown connection(s)" },
+    Numeric { code: command_replies::RPL_LUSERCHANNELS, name: "RPL_LUSERCHANNELS", format: "<integer> :channels formed" },
+    Numeric { code: command_replies::RPL_LUSERME, name: "RPL_LUSERME", format: ":I have <integer> clients and <integer> servers" },
+    Numeric { code: command_replies::RPL_ADMINME, name: "RPL_ADMINME", format: "<server> :Administrative info" },
+    Numeric { code: command_replies::RPL_ADMINLOC1, name: "RPL_ADMINLOC1", format: ":<admin info>" },
+    Numeric { code: command_replies::RPL_ADMINLOC2, name: "RPL_ADMINLOC2", format: ":<admin info>" },
+    Numeric { code: command_replies::RPL_ADMINEMAIL, name: "RPL_ADMINEMAIL", format: ":<admin info>" },
+    Numeric { code: error_replies::ERR_NOSUCHNICK, name: "ERR_NOSUCHNICK", format: "<nickname> :No such nick/channel" },
+    Numeric { code: error_replies::ERR_NOSUCHSERVER, name: "ERR_NOSUCHSERVER", format: "<server name> :No such server" },
+    Numeric { code: error_replies::ERR_NOSUCHCHANNEL, name: "ERR_NOSUCHCHANNEL", format: "<channel name> :No such channel" },
+    Numeric { code: error_replies::ERR_CANNOTSENDTOCHAN, name: "ERR_CANNOTSENDTOCHAN", format: "<channel name> :Cannot send to channel" },
+    Numeric { code: error_replies::ERR_TOOMANYCHANNELS, name: "ERR_TOOMANYCHANNELS", format: "<channel name> :You have joined too many channels" },
+    Numeric { code: error_replies::ERR_WASNOSUCHNICK, name: "ERR_WASNOSUCHNICK", format: "<nickname> :There was no such nickname" },
+    Numeric { code: error_replies::ERR_TOOMANYTARGETS, name: "ERR_TOOMANYTARGETS", format: "<target> :Duplicate recipients. No message delivered" },
+    Numeric { code: error_replies::ERR_NOORIGIN, name: "ERR_NOORIGIN", format: ":No origin specified" },
+    Numeric { code: error_replies::ERR_NORECIPIENT, name: "ERR_NORECIPIENT", format: ":No recipient given (<command>)" },
+    Numeric { code: error_replies::ERR_NOTEXTTOSEND, name: "ERR_NOTEXTTOSEND", format: ":No text to send" },
+    Numeric { code: error_replies::ERR_NOTOPLEVEL, name: "ERR_NOTOPLEVEL", format: "<mask> :No toplevel domain specified" },
+    Numeric { code: error_replies::ERR_WILDTOPLEVEL, name: "ERR_WILDTOPLEVEL", format: "<mask> :Wildcard in toplevel domain" },
+    Numeric { code: error_replies::ERR_UNKNOWNCOMMAND, name: "ERR_UNKNOWNCOMMAND", format: "<command> :Unknown command" },
+    Numeric { code: error_replies::ERR_NOMOTD, name: "ERR_NOMOTD", format: ":MOTD File is missing" },
+    Numeric { code: error_replies::ERR_NOADMININFO, name: "ERR_NOADMININFO", format: "<server> :No administrative info available" },
+    Numeric { code: error_replies::ERR_FILEERROR, name: "ERR_FILEERROR", format: ":File error doing <file op> on <file>" },
+    Numeric { code: error_replies::ERR_NONICKNAMEGIVEN, name: "ERR_NONICKNAMEGIVEN", format: ":No nickname given" },
+    Numeric { code: error_replies::ERR_ERRONEUSNICKNAME, name: "ERR_ERRONEUSNICKNAME", format: "<nick> :Erroneus nickname" },
+    Numeric { code: error_replies::ERR_NICKNAMEINUSE, name: "ERR_NICKNAMEINUSE", format: "<nick> :Nickname is already in use" },
+    Numeric { code: error_replies::ERR_NICKCOLLISION, name: "ERR_NICKCOLLISION", format: "<nick> :Nickname collision KILL" },
+    Numeric { code: error_replies::ERR_USERNOTINCHANNEL, name: "ERR_USERNOTINCHANNEL", format: "<nick> <channel> :They aren't on that channel" },
+    Numeric { code: error_replies::ERR_NOTONCHANNEL, name: "ERR_NOTONCHANNEL", format: "<channel> :You're not on that channel" },
+    Numeric { code: error_replies::ERR_USERONCHANNEL, name: "ERR_USERONCHANNEL", format: "<user> <channel> :is already on channel" },
+    Numeric { code: error_replies::ERR_NOLOGIN, name: "ERR_NOLOGIN", format: "<user> :User not logged in" },
+    Numeric { code: error_replies::ERR_SUMMONDISABLED, name: "ERR_SUMMONDISABLED", format: ":SUMMON has been disabled" },
+    Numeric { code: error_replies::ERR_USERSDISABLED, name: "ERR_USERSDISABLED", format: ":USERS has been disabled" },
+    Numeric { code: error_replies::ERR_NOTREGISTERED, name: "ERR_NOTREGISTERED", format: ":You have not registered" },
+    Numeric { code: error_replies::ERR_NEEDMOREPARAMS, name: "ERR_NEEDMOREPARAMS", format: "<command> :Not enough parameters" },
+    Numeric { code: error_replies::ERR_ALREADYREGISTRED, name: "ERR_ALREADYREGISTRED", format: ":You may not reregister" },
+    Numeric { code: error_replies::ERR_NOPERMFORHOST, name: "ERR_NOPERMFORHOST", format: ":Your host isn't among the privileged" },
+    Numeric { code: error_replies::ERR_PASSWDMISMATCH, name: "ERR_PASSWDMISMATCH", format: ":Password incorrect" },
+    Numeric { code: error_replies::ERR_YOUREBANNEDCREEP, name: "ERR_YOUREBANNEDCREEP", format: ":You are banned from this server" },
+    Numeric { code: error_replies::ERR_KEYSET, name: "ERR_KEYSET", format: "<channel> :Channel key already set" },
+    Numeric { code: error_replies::ERR_CHANNELISFULL, name: "ERR_CHANNELISFULL", format: "<channel> :Cannot join channel (+l)" },
+    Numeric { code: error_replies::ERR_UNKNOWNMODE, name: "ERR_UNKNOWNMODE", format: "<char> :is unknown mode char to me" },
+    Numeric { code: error_replies::ERR_INVITEONLYCHAN, name: "ERR_INVITEONLYCHAN", format: "<channel> :Cannot join channel (+i)" },
+    Numeric { code: error_replies::ERR_BANNEDFROMCHAN, name: "ERR_BANNEDFROMCHAN", format: "<channel> :Cannot join channel (+b)" },
+    Numeric { code: error_replies::ERR_BADCHANNELKEY, name: "ERR_BADCHANNELKEY", format: "<channel> :Cannot join channel (+k)" },
+    Numeric { code: error_replies::ERR_NOPRIVILEGES, name: "ERR_NOPRIVILEGES", format: ":Permission Denied- You're not an IRC operator" },
+    Numeric { code: error_replies::ERR_CHANOPRIVSNEEDED, name: "ERR_CHANOPRIVSNEEDED", format: "<channel> :You're not channel operator" },
+    Numeric { code: error_replies::ERR_CANTKILLSERVER, name: "ERR_CANTKILLSERVER", format: ":You cant kill a server!" },
+    Numeric { code: error_replies::ERR_NOOPERHOST, name: "ERR_NOOPERHOST", format: ":No O-lines for your host" },
+    Numeric { code: error_replies::ERR_UMODEUNKNOWNFLAG, name: "ERR_UMODEUNKNOWNFLAG", format: ":Unknown MODE flag" },
+    Numeric { code: error_replies::ERR_USERSDONTMATCH, name: "ERR_USERSDONTMATCH", format: ":Cant change mode for other users" },
+];
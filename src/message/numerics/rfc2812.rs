@@ -0,0 +1,85 @@
+//! Replies introduced by RFC 2812 on top of the ones from RFC 1459.
+
+use super::Numeric;
+
+/// The server sends Replies 001 to 004 to a user upon
+/// successful registration.
+pub const RPL_WELCOME: u32 = 1;  // ":Welcome to the Internet Relay Network <nick>!<user>@<host>"
+pub const RPL_YOURHOST: u32 = 2;  // ":Your host is <servername>, running version <ver>"
+pub const RPL_CREATED: u32 = 3;  // ":This server was created <date>"
+pub const RPL_MYINFO: u32 = 4;  // "<servername> <version> <available user modes> <available channel modes>"
+
+/// Sent by the server to a user to suggest an alternative
+/// server.  This is often used when the connection is
+/// refused because the server is already full.
+pub const RPL_BOUNCE: u32 = 5;  // ":Try server <server name>, port <port number>"
+
+/// When a server drops a command without processing it,
+/// it MUST use the reply RPL_TRYAGAIN to inform the
+/// originating client.
+pub const RPL_TRYAGAIN: u32 = 263;  // "<command> :Please wait a while and try again."
+
+/// When listing the 'invitations masks' for a given channel,
+/// a server is required to send the list back using the
+/// RPL_INVITELIST and RPL_ENDOFINVITELIST messages.
+pub const RPL_INVITELIST: u32 = 346;  // "<channel> <invitemask>"
+pub const RPL_ENDOFINVITELIST: u32 = 347;  // "<channel> :End of channel invite list"
+
+/// When listing the 'exception masks' for a given channel,
+/// a server is required to send the list back using the
+/// RPL_EXCEPTLIST and RPL_ENDOFEXCEPTLIST messages.
+pub const RPL_EXCEPTLIST: u32 = 348;  // "<channel> <exceptionmask>"
+pub const RPL_ENDOFEXCEPTLIST: u32 = 349;  // "<channel> :End of channel exception list"
+
+/// Sent by the server to a service upon successful
+/// registration.
+pub const RPL_YOURESERVICE: u32 = 383;  // "You are service <servicename>"
+
+/// Returned to a client which is attempting to send a SQUERY
+/// to a service which does not exist.
+pub const ERR_NOSUCHSERVICE: u32 = 408;  // "<service name> :No such service"
+
+/// Returned when an invalid mask was passed to "PRIVMSG $<server>"
+/// or "PRIVMSG #<host>".
+pub const ERR_BADMASK: u32 = 415;  // "<mask> :Bad Server/host mask"
+
+/// Returned by a server to a user trying to join a channel
+/// currently blocked by the channel delay mechanism, or to
+/// change the nickname while it is temporarily unavailable.
+pub const ERR_UNAVAILRESOURCE: u32 = 437;  // "<nick/channel> :Nick/channel is temporarily unavailable"
+
+pub const ERR_BADCHANMASK: u32 = 476;  // "<channel> :Bad Channel Mask"
+pub const ERR_NOCHANMODES: u32 = 477;  // "<channel> :Channel doesn't support modes"
+pub const ERR_BANLISTFULL: u32 = 478;  // "<channel> <char> :Channel list is full"
+
+/// Sent by the server to a user upon connection to indicate
+/// the restricted nature of the connection (user mode "+r").
+pub const ERR_RESTRICTED: u32 = 484;  // ":Your connection is restricted!"
+
+/// Any MODE requiring "channel creator" privileges MUST
+/// return this error if the client making the attempt is not
+/// a chanop on the specified channel.
+pub const ERR_UNIQOPPRIVSNEEDED: u32 = 485;  // ":You're not the original channel operator"
+
+/// All the replies above, for `numerics::lookup`.
+pub static NUMERICS: &'static [Numeric] = &[
+    Numeric { code: RPL_WELCOME, name: "RPL_WELCOME", format: ":Welcome to the Internet Relay Network <nick>!<user>@<host>" },
+    Numeric { code: RPL_YOURHOST, name: "RPL_YOURHOST", format: ":Your host is <servername>, running version <ver>" },
+    Numeric { code: RPL_CREATED, name: "RPL_CREATED", format: ":This server was created <date>" },
+    Numeric { code: RPL_MYINFO, name: "RPL_MYINFO", format: "<servername> <version> <available user modes> <available channel modes>" },
+    Numeric { code: RPL_BOUNCE, name: "RPL_BOUNCE", format: ":Try server <server name>, port <port number>" },
+    Numeric { code: RPL_TRYAGAIN, name: "RPL_TRYAGAIN", format: "<command> :Please wait a while and try again." },
+    Numeric { code: RPL_INVITELIST, name: "RPL_INVITELIST", format: "<channel> <invitemask>" },
+    Numeric { code: RPL_ENDOFINVITELIST, name: "RPL_ENDOFINVITELIST", format: "<channel> :End of channel invite list" },
+    Numeric { code: RPL_EXCEPTLIST, name: "RPL_EXCEPTLIST", format: "<channel> <exceptionmask>" },
+    Numeric { code: RPL_ENDOFEXCEPTLIST, name: "RPL_ENDOFEXCEPTLIST", format: "<channel> :End of channel exception list" },
+    Numeric { code: RPL_YOURESERVICE, name: "RPL_YOURESERVICE", format: "You are service <servicename>" },
+    Numeric { code: ERR_NOSUCHSERVICE, name: "ERR_NOSUCHSERVICE", format: "<service name> :No such service" },
+    Numeric { code: ERR_BADMASK, name: "ERR_BADMASK", format: "<mask> :Bad Server/host mask" },
+    Numeric { code: ERR_UNAVAILRESOURCE, name: "ERR_UNAVAILRESOURCE", format: "<nick/channel> :Nick/channel is temporarily unavailable" },
+    Numeric { code: ERR_BADCHANMASK, name: "ERR_BADCHANMASK", format: "<channel> :Bad Channel Mask" },
+    Numeric { code: ERR_NOCHANMODES, name: "ERR_NOCHANMODES", format: "<channel> :Channel doesn't support modes" },
+    Numeric { code: ERR_BANLISTFULL, name: "ERR_BANLISTFULL", format: "<channel> <char> :Channel list is full" },
+    Numeric { code: ERR_RESTRICTED, name: "ERR_RESTRICTED", format: ":Your connection is restricted!" },
+    Numeric { code: ERR_UNIQOPPRIVSNEEDED, name: "ERR_UNIQOPPRIVSNEEDED", format: ":You're not the original channel operator" },
+];
@@ -0,0 +1,113 @@
+use std::fmt;
+
+use message::{Message, ParseError, Tag, Prefix};
+
+/// Counterpart of `Message` which owns its data, for messages which have to
+/// outlive the line they were parsed from or are built from dynamic data.
+/// The prefix is kept in its wire form.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OwnedMessage {
+    pub tags: Vec<Tag>,
+    pub prefix: Option<String>,
+    pub command: String,
+    pub arguments: Vec<String>,
+}
+
+impl OwnedMessage {
+    pub fn new(prefix: Option<String>, command: String, arguments: Vec<String>) -> OwnedMessage {
+        OwnedMessage {
+            tags: vec![],
+            prefix: prefix,
+            command: command,
+            arguments: arguments,
+        }
+    }
+
+    pub fn parse(line: &str) -> Result<OwnedMessage, ParseError> {
+        Message::new(line).map(OwnedMessage::from)
+    }
+
+    /// Borrows the message back as a `Message`, e.g. to serialize it.
+    pub fn as_message(&self) -> Message {
+        Message {
+            tags: self.tags.clone(),
+            prefix: self.prefix.as_ref().map(|p| Prefix::parse(p.as_str())),
+            command: self.command.as_str(),
+            arguments: self.arguments.iter().map(|a| a.as_str()).collect(),
+        }
+    }
+}
+
+impl<'a, 'b> From<&'b Message<'a>> for OwnedMessage {
+    fn from(message: &'b Message<'a>) -> OwnedMessage {
+        OwnedMessage {
+            tags: message.tags.clone(),
+            prefix: message.prefix.as_ref().map(|p| p.to_string()),
+            command: message.command.to_string(),
+            arguments: message.arguments.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+}
+
+impl<'a> From<Message<'a>> for OwnedMessage {
+    fn from(message: Message<'a>) -> OwnedMessage {
+        OwnedMessage::from(&message)
+    }
+}
+
+impl fmt::Display for OwnedMessage {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        fmt::Display::fmt(&self.as_message(), fmt)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    pub use message::{Message, ParseError, Tag, Prefix};
+    pub use super::OwnedMessage;
+    pub use hamcrest::{assert_that, is, not, none, equal_to};
+
+    describe! owned_message {
+        it "is created from a borrowed message" {
+            let line = ":nick!user@host PRIVMSG #chan :hello there".to_string();
+            let m = OwnedMessage::from(Message::new(line.as_str()).unwrap());
+            drop(line);
+
+            assert_that(m.prefix, is(equal_to(Some("nick!user@host".to_string()))));
+            assert_that(m.command.as_str(), is(equal_to("PRIVMSG")));
+            assert_that(m.arguments, is(equal_to(vec!["#chan".to_string(), "hello there".to_string()])));
+        }
+
+        it "keeps the tags" {
+            let m = OwnedMessage::parse("@msgid=42 PING x").unwrap();
+
+            assert_that(m.tags, is(equal_to(vec![Tag::new("msgid", Some("42"))])));
+            assert_that(m.to_string(), is(equal_to("@msgid=42 PING x".to_string())));
+        }
+
+        it "is parsed from a line" {
+            let m = OwnedMessage::parse("NICK test").unwrap();
+            assert_that(m, is(equal_to(OwnedMessage::new(None, "NICK".to_string(), vec!["test".to_string()]))));
+        }
+
+        it "passes parse errors through" {
+            assert_that(OwnedMessage::parse("").err(), is(equal_to(Some(ParseError::EmptyLine))));
+        }
+
+        it "is built from dynamic data" {
+            let command = format!("{:03}", 1);
+            let m = OwnedMessage::new(Some("srv".to_string()), command, vec!["test".to_string(), "Welcome".to_string()]);
+
+            assert_that(m.to_string(), is(equal_to(":srv 001 test Welcome".to_string())));
+        }
+
+        it "borrows back as a message" {
+            let m = OwnedMessage::parse(":irc.example.com PRIVMSG test :hi there").unwrap();
+            let b = m.as_message();
+
+            assert_that(b.prefix, is(equal_to(Some(Prefix::Server("irc.example.com")))));
+            assert_that(b.command, is(equal_to("PRIVMSG")));
+            assert_that(b.arguments, is(equal_to(vec!["test", "hi there"])));
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use std::fmt;
+
+/// Origin of a message, either a server or a user.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Prefix<'a> {
+    Server(&'a str),
+    User {
+        nick: &'a str,
+        user: Option<&'a str>,
+        host: Option<&'a str>,
+    },
+}
+
+impl<'a> Prefix<'a> {
+    ///    <prefix> ::= <servername> | <nick> [ '!' <user> ] [ '@' <host> ]
+    ///
+    /// A bare name is a server name if it has a dot in it, as nicknames
+    /// can't.
+    pub fn parse(raw: &'a str) -> Prefix<'a> {
+        let (rest, host) = match raw.find('@') {
+            Some(at) => (&raw[..at], Some(&raw[at+1..])),
+            None => (raw, None),
+        };
+        let (nick, user) = match rest.find('!') {
+            Some(bang) => (&rest[..bang], Some(&rest[bang+1..])),
+            None => (rest, None),
+        };
+
+        if user.is_none() && host.is_none() && nick.contains('.') {
+            Prefix::Server(nick)
+        } else {
+            Prefix::User { nick: nick, user: user, host: host }
+        }
+    }
+
+    /// Nickname of a user prefix.
+    pub fn nick(&self) -> Option<&'a str> {
+        match *self {
+            Prefix::Server(_) => None,
+            Prefix::User { nick, .. } => Some(nick),
+        }
+    }
+
+    /// Whether the prefix can be written out and parsed back.
+    pub fn is_valid(&self) -> bool {
+        let valid = |s: &str| s.len() > 0 &&
+            !s.contains(|c: char| c == ' ' || c == '\0' || c == '\r' || c == '\n');
+
+        match *self {
+            Prefix::Server(name) => valid(name),
+            Prefix::User { nick, user, host } =>
+                valid(nick) && !nick.contains(|c: char| c == '!' || c == '@') &&
+                user.map_or(true, |u| valid(u) && !u.contains('@')) &&
+                host.map_or(true, |h| valid(h)),
+        }
+    }
+}
+
+impl<'a> fmt::Display for Prefix<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            Prefix::Server(name) => fmt.write_str(name),
+            Prefix::User { nick, user, host } => {
+                try!(fmt.write_str(nick));
+                if let Some(user) = user {
+                    try!(fmt.write_fmt(format_args!("!{}", user)));
+                }
+                if let Some(host) = host {
+                    try!(fmt.write_fmt(format_args!("@{}", host)));
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    pub use super::Prefix;
+    pub use hamcrest::{assert_that, is, not, none, equal_to};
+
+    describe! prefix {
+        it "parses a server name" {
+            assert_that(Prefix::parse("irc.example.com"), is(equal_to(Prefix::Server("irc.example.com"))));
+        }
+
+        it "parses a full user mask" {
+            assert_that(Prefix::parse("nick!user@host.example.com"), is(equal_to(Prefix::User {
+                nick: "nick", user: Some("user"), host: Some("host.example.com") })));
+        }
+
+        it "parses a nick with host only" {
+            assert_that(Prefix::parse("nick@host"), is(equal_to(Prefix::User {
+                nick: "nick", user: None, host: Some("host") })));
+        }
+
+        it "parses a bare nick" {
+            assert_that(Prefix::parse("nick"), is(equal_to(Prefix::User {
+                nick: "nick", user: None, host: None })));
+        }
+
+        it "returns the nick of a user" {
+            assert_that(Prefix::parse("nick!user@host").nick(), is(equal_to(Some("nick"))));
+            assert_that(Prefix::parse("irc.example.com").nick(), is(none()));
+        }
+
+        it "formats back to the original form" {
+            for raw in ["irc.example.com", "nick!user@host", "nick@host", "nick"].iter() {
+                assert_that(Prefix::parse(raw).to_string(), is(equal_to(raw.to_string())));
+            }
+        }
+
+        it "validates its parts" {
+            assert_that(Prefix::User { nick: "a b", user: None, host: None }.is_valid(), is(equal_to(false)));
+            assert_that(Prefix::User { nick: "a", user: Some(""), host: None }.is_valid(), is(equal_to(false)));
+            assert_that(Prefix::Server("").is_valid(), is(equal_to(false)));
+            assert_that(Prefix::Server("irc.example.com").is_valid(), is(equal_to(true)));
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use message::OwnedMessage;
+use message::numerics::rfc1459::error_replies::*;
+use message::numerics::rfc1459::command_replies::*;
+use message::numerics::rfc2812::{RPL_WELCOME, RPL_YOURHOST, RPL_CREATED, RPL_MYINFO};
+use message::numerics::modern::{RPL_ISUPPORT, RPL_LOCALUSERS, RPL_GLOBALUSERS, ERR_INVALIDCAPCMD,
+    ERR_INPUTTOOLONG, ERR_SASLFAIL};
+
+/// Numeric replies, each with the fields its format needs. A reply is
+/// addressed to a target (the nickname of the client or `*`) and sent from
+/// the server, both are supplied when rendering.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Reply<'a> {
+    /// 001 ":Welcome to the <network> Internet Relay Chat Network <nick>"
+    Welcome { network: &'a str, nick: &'a str },
+    YourHost { server: &'a str, version: &'a str },
+    Created { date: &'a str },
+    MyInfo { server: &'a str, version: &'a str, user_modes: &'a str, channel_modes: &'a str },
+    ISupport { tokens: &'a [String] },
+    LuserClient { users: usize, invisible: usize, servers: usize },
+    LuserOp { count: usize },
+    LuserUnknown { count: usize },
+    LuserChannels { count: usize },
+    LuserMe { clients: usize, servers: usize },
+    LocalUsers { current: usize, max: usize },
+    GlobalUsers { current: usize, max: usize },
+    MotdStart { server: &'a str },
+    Motd { line: &'a str },
+    EndOfMotd,
+    NoMotd,
+    WhoisUser { nick: &'a str, user: &'a str, host: &'a str, realname: &'a str },
+    WhoisServer { nick: &'a str, server: &'a str, info: &'a str },
+    EndOfWhois { nick: &'a str },
+    NoSuchNick { nick: &'a str },
+    CannotSendToChan { channel: &'a str },
+    TooManyTargets { target: &'a str },
+    NoOrigin,
+    NoRecipient { command: &'a str },
+    NoTextToSend,
+    InvalidCapCmd { subcommand: &'a str },
+    /// 417 ":Input line was too long"
+    InputTooLong,
+    UnknownCommand { command: &'a str },
+    NoNicknameGiven,
+    ErroneusNickname { nick: &'a str },
+    NicknameInUse { nick: &'a str },
+    NotRegistered,
+    NeedMoreParams { command: &'a str },
+    AlreadyRegistred,
+    PasswdMismatch,
+    NoPrivileges,
+    SaslFail,
+}
+
+impl<'a> Reply<'a> {
+    pub fn code(&self) -> u32 {
+        match *self {
+            Reply::Welcome { .. } => RPL_WELCOME,
+            Reply::YourHost { .. } => RPL_YOURHOST,
+            Reply::Created { .. } => RPL_CREATED,
+            Reply::MyInfo { .. } => RPL_MYINFO,
+            Reply::ISupport { .. } => RPL_ISUPPORT,
+            Reply::LuserClient { .. } => RPL_LUSERCLIENT,
+            Reply::LuserOp { .. } => RPL_LUSEROP,
+            Reply::LuserUnknown { .. } => RPL_LUSERUNKNOWN,
+            Reply::LuserChannels { .. } => RPL_LUSERCHANNELS,
+            Reply::LuserMe { .. } => RPL_LUSERME,
+            Reply::LocalUsers { .. } => RPL_LOCALUSERS,
+            Reply::GlobalUsers { .. } => RPL_GLOBALUSERS,
+            Reply::MotdStart { .. } => RPL_MOTDSTART,
+            Reply::Motd { .. } => RPL_MOTD,
+            Reply::EndOfMotd => RPL_ENDOFMOTD,
+            Reply::NoMotd => ERR_NOMOTD,
+            Reply::WhoisUser { .. } => RPL_WHOISUSER,
+            Reply::WhoisServer { .. } => RPL_WHOISSERVER,
+            Reply::EndOfWhois { .. } => RPL_ENDOFWHOIS,
+            Reply::NoSuchNick { .. } => ERR_NOSUCHNICK,
+            Reply::CannotSendToChan { .. } => ERR_CANNOTSENDTOCHAN,
+            Reply::TooManyTargets { .. } => ERR_TOOMANYTARGETS,
+            Reply::NoOrigin => ERR_NOORIGIN,
+            Reply::NoRecipient { .. } => ERR_NORECIPIENT,
+            Reply::NoTextToSend => ERR_NOTEXTTOSEND,
+            Reply::InvalidCapCmd { .. } => ERR_INVALIDCAPCMD,
+            Reply::InputTooLong => ERR_INPUTTOOLONG,
+            Reply::UnknownCommand { .. } => ERR_UNKNOWNCOMMAND,
+            Reply::NoNicknameGiven => ERR_NONICKNAMEGIVEN,
+            Reply::ErroneusNickname { .. } => ERR_ERRONEUSNICKNAME,
+            Reply::NicknameInUse { .. } => ERR_NICKNAMEINUSE,
+            Reply::NotRegistered => ERR_NOTREGISTERED,
+            Reply::NeedMoreParams { .. } => ERR_NEEDMOREPARAMS,
+            Reply::AlreadyRegistred => ERR_ALREADYREGISTRED,
+            Reply::PasswdMismatch => ERR_PASSWDMISMATCH,
+            Reply::NoPrivileges => ERR_NOPRIVILEGES,
+            Reply::SaslFail => ERR_SASLFAIL,
+        }
+    }
+
+    /// Arguments of the reply following the target.
+    pub fn arguments(&self) -> Vec<String> {
+        let args: Vec<&str> = match *self {
+            Reply::Welcome { network, nick } => return vec![
+                format!("Welcome to the {} Internet Relay Chat Network {}", network, nick)],
+            Reply::YourHost { server, version } => return vec![
+                format!("Your host is {}, running version {}", server, version)],
+            Reply::Created { date } => return vec![format!("This server was created {}", date)],
+            Reply::MyInfo { server, version, user_modes, channel_modes } =>
+                vec![server, version, user_modes, channel_modes],
+            Reply::ISupport { tokens } => {
+                let mut args = tokens.to_vec();
+                args.push("are supported by this server".to_string());
+                return args;
+            },
+            Reply::LuserClient { users, invisible, servers } => return vec![
+                format!("There are {} users and {} invisible on {} servers", users, invisible, servers)],
+            Reply::LuserOp { count } => return vec![count.to_string(), "operator(s) online".to_string()],
+            Reply::LuserUnknown { count } => return vec![count.to_string(), "unknown connection(s)".to_string()],
+            Reply::LuserChannels { count } => return vec![count.to_string(), "channels formed".to_string()],
+            Reply::LuserMe { clients, servers } => return vec![
+                format!("I have {} clients and {} servers", clients, servers)],
+            Reply::LocalUsers { current, max } => return vec![current.to_string(), max.to_string(),
+                format!("Current local users {}, max {}", current, max)],
+            Reply::GlobalUsers { current, max } => return vec![current.to_string(), max.to_string(),
+                format!("Current global users {}, max {}", current, max)],
+            Reply::MotdStart { server } => return vec![format!("- {} Message of the day - ", server)],
+            Reply::Motd { line } => return vec![format!("- {}", line)],
+            Reply::EndOfMotd => vec!["End of /MOTD command"],
+            Reply::NoMotd => vec!["MOTD File is missing"],
+            Reply::WhoisUser { nick, user, host, realname } => vec![nick, user, host, "*", realname],
+            Reply::WhoisServer { nick, server, info } => vec![nick, server, info],
+            Reply::EndOfWhois { nick } => vec![nick, "End of /WHOIS list"],
+            Reply::NoSuchNick { nick } => vec![nick, "No such nick/channel"],
+            Reply::CannotSendToChan { channel } => vec![channel, "Cannot send to channel"],
+            Reply::TooManyTargets { target } => vec![target, "Too many targets. Message not delivered"],
+            Reply::NoOrigin => vec!["No origin specified"],
+            Reply::NoRecipient { command } => return vec![format!("No recipient given ({})", command)],
+            Reply::NoTextToSend => vec!["No text to send"],
+            Reply::InvalidCapCmd { subcommand } => vec![subcommand, "Invalid CAP command"],
+            Reply::InputTooLong => vec!["Input line was too long"],
+            Reply::UnknownCommand { command } => vec![command, "Unknown command"],
+            Reply::NoNicknameGiven => vec!["No nickname given"],
+            Reply::ErroneusNickname { nick } => vec![nick, "Erroneus nickname"],
+            Reply::NicknameInUse { nick } => vec![nick, "Nickname is already in use"],
+            Reply::NotRegistered => vec!["You have not registered"],
+            Reply::NeedMoreParams { command } => vec![command, "Not enough parameters"],
+            Reply::AlreadyRegistred => vec!["You may not reregister"],
+            Reply::PasswdMismatch => vec!["Password incorrect"],
+            Reply::NoPrivileges => vec!["Permission Denied- You're not an IRC operator"],
+            Reply::SaslFail => vec!["SASL authentication failed"],
+        };
+        args.into_iter().map(|a| a.to_string()).collect()
+    }
+
+    pub fn to_message(&self, server_name: &str, target: &str) -> OwnedMessage {
+        let mut arguments = vec![target.to_string()];
+        arguments.extend(self.arguments().into_iter());
+
+        OwnedMessage::new(Some(server_name.to_string()), format!("{:03}", self.code()), arguments)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    pub use super::Reply;
+    pub use message::error::IRCError;
+    pub use hamcrest::{assert_that, is, not, none, equal_to};
+
+    describe! reply {
+        it "renders RPL_WELCOME" {
+            let reply = Reply::Welcome { network: "TestLocal", nick: "test" };
+
+            assert_that(reply.to_message("test.local", "test").to_string(),
+                is(equal_to(":test.local 001 test :Welcome to the TestLocal Internet Relay Chat Network test".to_string())));
+        }
+
+        it "renders RPL_ISUPPORT" {
+            let tokens = vec!["CASEMAPPING=rfc1459".to_string(), "NICKLEN=30".to_string()];
+            let reply = Reply::ISupport { tokens: &tokens };
+
+            assert_that(reply.to_message("test.local", "test").to_string(), is(equal_to(
+                ":test.local 005 test CASEMAPPING=rfc1459 NICKLEN=30 :are supported by this server".to_string())));
+        }
+
+        it "renders RPL_LOCALUSERS" {
+            let reply = Reply::LocalUsers { current: 2, max: 5 };
+
+            assert_that(reply.to_message("test.local", "test").to_string(), is(equal_to(
+                ":test.local 265 test 2 5 :Current local users 2, max 5".to_string())));
+        }
+
+        it "renders RPL_WHOISUSER" {
+            let reply = Reply::WhoisUser { nick: "test", user: "guest", host: "127.0.0.1", realname: "Ronnie Reagan" };
+
+            assert_that(reply.to_message("test.local", "other").to_string(),
+                is(equal_to(":test.local 311 other test guest 127.0.0.1 * :Ronnie Reagan".to_string())));
+        }
+
+        it "renders ERR_NOSUCHNICK" {
+            let reply = Reply::NoSuchNick { nick: "nobody" };
+
+            assert_that(reply.code(), is(equal_to(401)));
+            assert_that(reply.to_message("test.local", "test").to_string(),
+                is(equal_to(":test.local 401 test nobody :No such nick/channel".to_string())));
+        }
+
+        it "renders to an unregistered target" {
+            let reply = Reply::NeedMoreParams { command: "USER" };
+
+            assert_that(reply.to_message("test.local", "*").to_string(),
+                is(equal_to(":test.local 461 * USER :Not enough parameters".to_string())));
+        }
+
+        it "exposes structured arguments" {
+            let reply = Reply::NoRecipient { command: "PRIVMSG" };
+
+            assert_that(reply.arguments(), is(equal_to(vec!["No recipient given (PRIVMSG)".to_string()])));
+        }
+
+        it "converts into an IRCError" {
+            let error = IRCError::from(Reply::NicknameInUse { nick: "test" });
+
+            assert_that(error.error_code(), is(equal_to(433)));
+            assert_that(error.to_message("test.local", "*").map(|m| m.to_string()),
+                is(equal_to(Some(":test.local 433 * test :Nickname is already in use".to_string()))));
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use std::fmt;
+use std::ascii::AsciiExt;
+
+use message::ParseError;
+
+/// Clients may send at most this many bytes of tags, including the leading
+/// '@' and the space following them.
+pub const MAX_CLIENT_TAGS_LENGTH: usize = 4094;
+
+/// IRCv3 message tag. A tag with an empty value is the same as a tag without
+/// one, so the value is never `Some("")`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tag {
+    pub key: String,
+    pub value: Option<String>,
+}
+
+impl Tag {
+    pub fn new(key: &str, value: Option<&str>) -> Tag {
+        Tag {
+            key: key.to_string(),
+            value: value.and_then(|v| if v.len() > 0 { Some(v.to_string()) } else { None }),
+        }
+    }
+
+    /// Client-only tags are prefixed with '+' and are relayed as is.
+    pub fn is_client_only(&self) -> bool {
+        self.key.starts_with("+")
+    }
+
+    ///    <key>    ::= [ <client_prefix> ] [ <vendor> '/' ] <key_name>
+    ///    <key_name> ::= <non-empty sequence of ascii letters, digits, hyphens ('-')>
+    ///    <vendor> ::= <host>
+    pub fn is_valid_key(key: &str) -> bool {
+        let key = if key.starts_with("+") { &key[1..] } else { key };
+        let (vendor, name) = match key.rfind('/') {
+            Some(slash) => (&key[..slash], &key[slash+1..]),
+            None => ("", key),
+        };
+
+        name.len() > 0 &&
+            name.chars().all(|c| c.is_alphanumeric() && c.is_ascii() || c == '-') &&
+            vendor.chars().all(|c| c.is_alphanumeric() && c.is_ascii() || c == '-' || c == '.')
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        try!(fmt.write_str(self.key.as_str()));
+        if let Some(ref value) = self.value {
+            try!(fmt.write_fmt(format_args!("={}", escape_value(value))));
+        }
+        Ok(())
+    }
+}
+
+pub fn escape_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            ';' => escaped.push_str("\\:"),
+            ' ' => escaped.push_str("\\s"),
+            '\\' => escaped.push_str("\\\\"),
+            '\r' => escaped.push_str("\\r"),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Unknown escapes stand for the escaped character itself, a lone trailing
+/// backslash is dropped.
+pub fn unescape_value(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some(':') => unescaped.push(';'),
+            Some('s') => unescaped.push(' '),
+            Some('r') => unescaped.push('\r'),
+            Some('n') => unescaped.push('\n'),
+            Some(c) => unescaped.push(c),
+            None => (),
+        }
+    }
+    unescaped
+}
+
+/// Parses the tags section of a line, without the leading '@'.
+pub fn parse_tags(raw: &str) -> Result<Vec<Tag>, ParseError> {
+    let mut tags = Vec::new();
+
+    for item in raw.split(';') {
+        if item.len() == 0 {
+            continue;
+        }
+        let (key, value) = match item.find('=') {
+            Some(eq) => (&item[..eq], Some(unescape_value(&item[eq+1..]))),
+            None => (item, None),
+        };
+        if !Tag::is_valid_key(key) {
+            return Err(ParseError::InvalidTags);
+        }
+        tags.push(Tag::new(key, value.as_ref().map(|v| v.as_str())));
+    }
+
+    if tags.is_empty() {
+        Err(ParseError::InvalidTags)
+    } else {
+        Ok(tags)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    pub use message::ParseError;
+    pub use super::{Tag, parse_tags, escape_value, unescape_value};
+    pub use hamcrest::{assert_that, is, not, none, equal_to};
+
+    describe! tags {
+        it "parses keys with and without values" {
+            assert_that(parse_tags("aaa=bbb;ccc;example.com/ddd=eee").unwrap(), is(equal_to(vec![
+                Tag::new("aaa", Some("bbb")),
+                Tag::new("ccc", None),
+                Tag::new("example.com/ddd", Some("eee"))])));
+        }
+
+        it "treats an empty value as no value" {
+            assert_that(parse_tags("a=").unwrap(), is(equal_to(vec![Tag::new("a", None)])));
+        }
+
+        it "parses client-only tags" {
+            let tags = parse_tags("+example.com/typing=active").unwrap();
+
+            assert_that(tags[0].is_client_only(), is(equal_to(true)));
+            assert_that(tags[0].key.as_str(), is(equal_to("+example.com/typing")));
+        }
+
+        it "unescapes values" {
+            let tags = parse_tags("a=one\\:two\\sthree\\\\four\\r\\n").unwrap();
+
+            assert_that(tags[0].value, is(equal_to(Some("one;two three\\four\r\n".to_string()))));
+        }
+
+        it "handles unknown escapes and a trailing backslash" {
+            assert_that(unescape_value("\\b\\"), is(equal_to("b".to_string())));
+        }
+
+        it "escapes values" {
+            assert_that(escape_value("one;two three\\four\r\n"),
+                is(equal_to("one\\:two\\sthree\\\\four\\r\\n".to_string())));
+        }
+
+        it "writes tags back" {
+            let tag = Tag::new("example.com/x", Some("a b"));
+            assert_that(tag.to_string(), is(equal_to("example.com/x=a\\sb".to_string())));
+
+            let tag = Tag::new("x", None);
+            assert_that(tag.to_string(), is(equal_to("x".to_string())));
+        }
+
+        it "fails on invalid keys" {
+            assert_that(parse_tags("a b=c").err(), is(equal_to(Some(ParseError::InvalidTags))));
+            assert_that(parse_tags("=c").err(), is(equal_to(Some(ParseError::InvalidTags))));
+            assert_that(parse_tags("vendor/").err(), is(equal_to(Some(ParseError::InvalidTags))));
+        }
+
+        it "fails on an empty tags section" {
+            assert_that(parse_tags("").err(), is(equal_to(Some(ParseError::InvalidTags))));
+        }
+    }
+}
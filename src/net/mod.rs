@@ -0,0 +1,259 @@
+use std::collections::HashMap;
+use std::io;
+use std::io::Write;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use mio;
+use mio::{EventLoop, EventSet, Handler, PollOpt};
+use mio::tcp::TcpListener;
+use openssl::ssl::SslContext;
+use openssl::ssl::error::SslError;
+
+use server::{Server, Token};
+use self::stream::{Stream, tls_context};
+
+pub mod stream;
+
+const READ_BUFFER_SIZE: usize = 4096;
+
+/// How often the server gets to check for ping and registration timeouts.
+const TICK_MS: u64 = 1000;
+
+/// Write half of a connection, handed over to `Server` as the client's
+/// out_socket. The daemon keeps another reference for reading.
+struct SharedSocket {
+    stream: Rc<RefCell<Stream>>,
+}
+
+impl io::Write for SharedSocket {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stream.borrow_mut().write(buf)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.borrow_mut().flush()
+    }
+}
+
+struct Listener {
+    socket: TcpListener,
+    tls: Option<SslContext>,
+}
+
+struct Connection {
+    stream: Rc<RefCell<Stream>>,
+    /// TLS details are only known after the handshake, they are passed to
+    /// the server along with the first data received.
+    handshake_done: bool,
+    /// Whether the socket is registered for writability, which is only
+    /// needed while the client has a SendQ backlog.
+    write_interest: bool,
+}
+
+/// Drives a `Server` from a mio event loop: accepts connections on every
+/// configured listener, assigns tokens and feeds received data into
+/// `Server::process_data`.
+pub struct Daemon {
+    server: Server,
+    listeners: Vec<Listener>,
+    connections: HashMap<Token, Connection>,
+    next_token: Token,
+}
+
+impl Daemon {
+    pub fn new(server: Server) -> Daemon {
+        Daemon {
+            server: server,
+            listeners: Vec::new(),
+            connections: HashMap::new(),
+            next_token: 0,
+        }
+    }
+
+    pub fn server(&self) -> &Server { &self.server }
+
+    /// Binds a new plaintext listener. Must be called before `run`.
+    pub fn listen(&mut self, addr: &SocketAddr) -> io::Result<()> {
+        let socket = try!(TcpListener::bind(addr));
+        self.listeners.push(Listener { socket: socket, tls: None });
+        Ok(())
+    }
+
+    /// Binds a new TLS listener using the PEM encoded certificate and key.
+    /// Must be called before `run`.
+    pub fn listen_tls(&mut self, addr: &SocketAddr, cert: &Path, key: &Path) -> Result<(), SslError> {
+        let ctx = try!(tls_context(cert, key));
+        let socket = try!(TcpListener::bind(addr).map_err(SslError::StreamError));
+        self.listeners.push(Listener { socket: socket, tls: Some(ctx) });
+        Ok(())
+    }
+
+    pub fn run(&mut self) -> io::Result<()> {
+        let mut event_loop = try!(EventLoop::new());
+
+        // Tokens below listeners.len() are reserved for the listeners
+        // themselves, clients are numbered after them.
+        for (index, listener) in self.listeners.iter().enumerate() {
+            try!(event_loop.register_opt(&listener.socket, mio::Token(index),
+                EventSet::readable(), PollOpt::level()));
+        }
+        self.next_token = self.listeners.len();
+
+        if event_loop.timeout_ms((), TICK_MS).is_err() {
+            return Err(io::Error::new(io::ErrorKind::Other, "failed to set up the timer"));
+        }
+
+        event_loop.run(self)
+    }
+
+    fn accept(&mut self, event_loop: &mut EventLoop<Daemon>, index: usize) {
+        loop {
+            let socket = match self.listeners[index].socket.accept() {
+                Ok(Some(socket)) => socket,
+                Ok(None) => return,
+                Err(_) => return,
+            };
+
+            let hostname = match socket.peer_addr() {
+                Ok(addr) => format!("{}", addr.ip()),
+                Err(_) => continue,
+            };
+
+            let stream = match self.listeners[index].tls {
+                Some(ref ctx) => match Stream::accept_tls(ctx, socket) {
+                    Ok(stream) => stream,
+                    Err(_) => continue,
+                },
+                None => Stream::Plain(socket),
+            };
+
+            let token = self.next_token;
+            self.next_token += 1;
+
+            if event_loop.register_opt(stream.socket(), mio::Token(token),
+                    EventSet::readable() | EventSet::hup(), PollOpt::level()).is_err() {
+                continue;
+            }
+
+            let stream = Rc::new(RefCell::new(stream));
+            let out_socket = Box::new(SharedSocket { stream: stream.clone() });
+            self.connections.insert(token, Connection {
+                stream: stream,
+                handshake_done: false,
+                write_interest: false,
+            });
+            self.server.accept_connection(out_socket, token, hostname);
+
+            // Reverse lookups are not done, clients go by their address.
+            self.server.send_notice(token, "*** Using your IP address as hostname");
+        }
+    }
+
+    fn read(&mut self, event_loop: &mut EventLoop<Daemon>, token: Token) {
+        let mut buf = [0u8; READ_BUFFER_SIZE];
+
+        loop {
+            let result = match self.connections.get(&token) {
+                Some(conn) => conn.stream.borrow_mut().try_read(&mut buf),
+                None => return,
+            };
+
+            if let Ok(Some(len)) = result {
+                if len > 0 {
+                    self.handshake_done(token);
+                }
+            }
+
+            match result {
+                Ok(Some(0)) => {
+                    self.server.disconnect(token, "Remote host closed the connection");
+                    return;
+                },
+                Err(e) => {
+                    self.server.disconnect(token, format!("Read error: {}", e).as_str());
+                    return;
+                },
+                Ok(Some(len)) => self.server.process_data(token, &buf[..len]),
+                Ok(None) => return,
+            }
+        }
+    }
+
+    fn handshake_done(&mut self, token: Token) {
+        let conn = self.connections.get_mut(&token).unwrap();
+        if conn.handshake_done {
+            return;
+        }
+        conn.handshake_done = true;
+
+        let stream = conn.stream.borrow();
+        if stream.is_secure() {
+            self.server.set_secure(token, stream.certfp());
+        }
+    }
+
+    /// Flushes the SendQs, asking for writability notifications for the
+    /// sockets which could not take everything.
+    fn flush(&mut self, event_loop: &mut EventLoop<Daemon>) {
+        let pending = self.server.flush();
+
+        for (token, conn) in self.connections.iter_mut() {
+            let want_write = pending.contains(token);
+            if want_write == conn.write_interest {
+                continue;
+            }
+
+            let interest = if want_write {
+                EventSet::readable() | EventSet::writable() | EventSet::hup()
+            } else {
+                EventSet::readable() | EventSet::hup()
+            };
+            if event_loop.reregister(conn.stream.borrow().socket(), mio::Token(*token),
+                    interest, PollOpt::level()).is_ok() {
+                conn.write_interest = want_write;
+            }
+        }
+    }
+
+    /// Drops the sockets of all the connections the server is done with.
+    fn close_dropped(&mut self, event_loop: &mut EventLoop<Daemon>) {
+        for token in self.server.drain_closed().into_iter() {
+            if let Some(conn) = self.connections.remove(&token) {
+                let _ = event_loop.deregister(conn.stream.borrow().socket());
+            }
+        }
+    }
+}
+
+impl Handler for Daemon {
+    type Timeout = ();
+    type Message = ();
+
+    fn ready(&mut self, event_loop: &mut EventLoop<Daemon>, token: mio::Token, events: EventSet) {
+        let token = token.as_usize();
+
+        if token < self.listeners.len() {
+            self.accept(event_loop, token);
+        } else {
+            if events.is_readable() {
+                self.read(event_loop, token);
+            }
+            if events.is_hup() || events.is_error() {
+                self.server.disconnect(token, "Connection reset by peer");
+            }
+        }
+
+        self.flush(event_loop);
+        self.close_dropped(event_loop);
+    }
+
+    fn timeout(&mut self, event_loop: &mut EventLoop<Daemon>, _: ()) {
+        self.server.check_timeouts();
+        self.flush(event_loop);
+        self.close_dropped(event_loop);
+
+        let _ = event_loop.timeout_ms((), TICK_MS);
+    }
+}
@@ -0,0 +1,100 @@
+use std::io;
+use std::path::Path;
+
+use mio::TryRead;
+use mio::tcp::TcpStream;
+use openssl::crypto::hash::Type as HashType;
+use openssl::ssl::{SslContext, SslMethod, NonblockingSslStream, SSL_VERIFY_PEER};
+use openssl::ssl::error::{SslError, NonblockingSslError};
+use openssl::x509::{X509FileType, X509StoreContext};
+
+/// Client connection, either plaintext or TLS.
+pub enum Stream {
+    Plain(TcpStream),
+    Tls(NonblockingSslStream<TcpStream>),
+}
+
+/// Client certificates are only used for identification (certfp), so any
+/// certificate is accepted at the TLS level.
+fn accept_any_certificate(_: bool, _: &X509StoreContext) -> bool {
+    true
+}
+
+/// Builds the context for a TLS listener from PEM encoded certificate and
+/// private key files.
+pub fn tls_context(cert: &Path, key: &Path) -> Result<SslContext, SslError> {
+    let mut ctx = try!(SslContext::new(SslMethod::Sslv23));
+    try!(ctx.set_certificate_file(cert, X509FileType::PEM));
+    try!(ctx.set_private_key_file(key, X509FileType::PEM));
+    ctx.set_verify(SSL_VERIFY_PEER, Some(accept_any_certificate as fn(bool, &X509StoreContext) -> bool));
+    Ok(ctx)
+}
+
+fn ssl_error(e: NonblockingSslError) -> io::Result<Option<usize>> {
+    match e {
+        NonblockingSslError::WantRead | NonblockingSslError::WantWrite => Ok(None),
+        NonblockingSslError::SslError(e) =>
+            Err(io::Error::new(io::ErrorKind::Other, format!("TLS error: {}", e))),
+    }
+}
+
+impl Stream {
+    pub fn accept_tls(ctx: &SslContext, socket: TcpStream) -> Result<Stream, SslError> {
+        let stream = try!(NonblockingSslStream::accept(ctx, socket));
+        Ok(Stream::Tls(stream))
+    }
+
+    pub fn socket(&self) -> &TcpStream {
+        match *self {
+            Stream::Plain(ref s) => s,
+            Stream::Tls(ref s) => s.get_ref(),
+        }
+    }
+
+    pub fn is_secure(&self) -> bool {
+        match *self {
+            Stream::Plain(_) => false,
+            Stream::Tls(_) => true,
+        }
+    }
+
+    /// Reads without blocking, `Ok(None)` means there is no data available
+    /// (or the TLS handshake is still in progress).
+    pub fn try_read(&mut self, buf: &mut [u8]) -> io::Result<Option<usize>> {
+        match *self {
+            Stream::Plain(ref mut s) => s.try_read(buf),
+            Stream::Tls(ref mut s) => s.try_read(buf).or_else(ssl_error),
+        }
+    }
+
+    /// Lowercase hex SHA-256 fingerprint of the client certificate, if the
+    /// client presented one.
+    pub fn certfp(&self) -> Option<String> {
+        match *self {
+            Stream::Plain(_) => None,
+            Stream::Tls(ref s) => s.ssl().peer_certificate()
+                .and_then(|cert| cert.fingerprint(HashType::SHA256))
+                .map(|fp| fp.iter().map(|b| format!("{:02x}", b)).collect::<Vec<String>>().concat()),
+        }
+    }
+}
+
+impl io::Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match *self {
+            Stream::Plain(ref mut s) => s.write(buf),
+            Stream::Tls(ref mut s) => match s.try_write(buf).or_else(ssl_error) {
+                Ok(Some(len)) => Ok(len),
+                Ok(None) => Err(io::Error::new(io::ErrorKind::WouldBlock, "TLS stream is blocked")),
+                Err(e) => Err(e),
+            },
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match *self {
+            Stream::Plain(ref mut s) => s.flush(),
+            Stream::Tls(_) => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::ascii::AsciiExt;
+
+/// Rules for comparing nicknames and channel names, as advertised in the
+/// CASEMAPPING token of RPL_ISUPPORT.
+///
+/// Because of IRC's Scandinavian origin, RFC 1459 considers the characters
+/// `{}|^` to be the lower case equivalents of `[]\~`. The strict variant
+/// leaves `^` and `~` alone.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CaseMapping {
+    Ascii,
+    Rfc1459,
+    StrictRfc1459,
+}
+
+impl CaseMapping {
+    pub fn from_name(name: &str) -> Option<CaseMapping> {
+        match name {
+            "ascii" => Some(CaseMapping::Ascii),
+            "rfc1459" => Some(CaseMapping::Rfc1459),
+            "strict-rfc1459" => Some(CaseMapping::StrictRfc1459),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            CaseMapping::Ascii => "ascii",
+            CaseMapping::Rfc1459 => "rfc1459",
+            CaseMapping::StrictRfc1459 => "strict-rfc1459",
+        }
+    }
+
+    /// RPL_ISUPPORT token advertising the casemapping.
+    pub fn isupport_token(&self) -> String {
+        format!("CASEMAPPING={}", self.name())
+    }
+
+    pub fn to_lower(&self, c: char) -> char {
+        match (*self, c) {
+            (CaseMapping::Rfc1459, '[') | (CaseMapping::StrictRfc1459, '[') => '{',
+            (CaseMapping::Rfc1459, ']') | (CaseMapping::StrictRfc1459, ']') => '}',
+            (CaseMapping::Rfc1459, '\\') | (CaseMapping::StrictRfc1459, '\\') => '|',
+            (CaseMapping::Rfc1459, '~') => '^',
+            _ => c.to_ascii_lowercase(),
+        }
+    }
+
+    /// Lower case form of the name, used as the key for lookups.
+    pub fn fold(&self, name: &str) -> String {
+        name.chars().map(|c| self.to_lower(c)).collect()
+    }
+
+    pub fn equals(&self, a: &str, b: &str) -> bool {
+        self.fold(a) == self.fold(b)
+    }
+}
+
+impl Default for CaseMapping {
+    fn default() -> CaseMapping { CaseMapping::Rfc1459 }
+}
+
+#[cfg(test)]
+mod test {
+    pub use super::CaseMapping;
+    pub use hamcrest::{assert_that, is, not, none, equal_to};
+
+    describe! casemapping {
+        it "folds ASCII letters in every mapping" {
+            for mapping in [CaseMapping::Ascii, CaseMapping::Rfc1459, CaseMapping::StrictRfc1459].iter() {
+                assert_that(mapping.fold("TeSt"), is(equal_to("test".to_string())));
+            }
+        }
+
+        it "leaves special characters alone in ascii" {
+            assert_that(CaseMapping::Ascii.fold("[]\\~"), is(equal_to("[]\\~".to_string())));
+        }
+
+        it "folds brackets in rfc1459" {
+            assert_that(CaseMapping::Rfc1459.fold("[]\\~"), is(equal_to("{}|^".to_string())));
+            assert_that(CaseMapping::Rfc1459.equals("Nick[a]", "nick{A}"), is(equal_to(true)));
+        }
+
+        it "does not fold the tilde in strict-rfc1459" {
+            assert_that(CaseMapping::StrictRfc1459.fold("[]\\~"), is(equal_to("{}|~".to_string())));
+        }
+
+        it "leaves non-ASCII characters alone" {
+            assert_that(CaseMapping::Rfc1459.fold("ÄÖ"), is(equal_to("ÄÖ".to_string())));
+        }
+
+        it "round-trips names" {
+            assert_that(CaseMapping::from_name("strict-rfc1459"), is(equal_to(Some(CaseMapping::StrictRfc1459))));
+            assert_that(CaseMapping::from_name("unicode"), is(none()));
+            assert_that(CaseMapping::Ascii.isupport_token(), is(equal_to("CASEMAPPING=ascii".to_string())));
+        }
+    }
+}
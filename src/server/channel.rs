@@ -0,0 +1,71 @@
+use std::collections::HashSet;
+
+use server::Token;
+
+/// Whether the target names a channel rather than a user.
+pub fn is_channel_name(name: &str) -> bool {
+    name.starts_with("#") || name.starts_with("&")
+}
+
+pub struct Channel {
+    name: String,
+    members: HashSet<Token>,
+}
+
+impl Channel {
+    pub fn new(name: String) -> Channel {
+        Channel {
+            name: name,
+            members: HashSet::new(),
+        }
+    }
+
+    pub fn name(&self) -> &String { &self.name }
+
+    pub fn add_member(&mut self, token: Token) { self.members.insert(token); }
+    pub fn remove_member(&mut self, token: Token) { self.members.remove(&token); }
+    pub fn has_member(&self, token: Token) -> bool { self.members.contains(&token) }
+    pub fn is_empty(&self) -> bool { self.members.is_empty() }
+
+    pub fn members(&self) -> Vec<Token> {
+        self.members.iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    pub use super::{Channel, is_channel_name};
+    pub use hamcrest::{assert_that, is, not, none, equal_to};
+
+    describe! channel {
+        before_each {
+            let mut chan = Channel::new("#test".to_string());
+        }
+
+        it "starts empty" {
+            assert_that(chan.is_empty(), is(equal_to(true)));
+        }
+
+        it "tracks added members" {
+            chan.add_member(8);
+            chan.add_member(8);
+
+            assert_that(chan.has_member(8), is(equal_to(true)));
+            assert_that(chan.members(), is(equal_to(vec![8])));
+        }
+
+        it "forgets removed members" {
+            chan.add_member(8);
+            chan.remove_member(8);
+
+            assert_that(chan.has_member(8), is(equal_to(false)));
+            assert_that(chan.is_empty(), is(equal_to(true)));
+        }
+
+        it "tells channel names from nicknames" {
+            assert_that(is_channel_name("#test"), is(equal_to(true)));
+            assert_that(is_channel_name("&local"), is(equal_to(true)));
+            assert_that(is_channel_name("test"), is(equal_to(false)));
+        }
+    }
+}
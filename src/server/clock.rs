@@ -0,0 +1,57 @@
+use std::rc::Rc;
+use std::cell::Cell;
+use time;
+
+/// Source of time for everything in the server which depends on it.
+pub trait Clock {
+    /// Milliseconds since an arbitrary, but fixed, point in the past.
+    fn now(&self) -> u64;
+}
+
+/// Monotonic clock of the host system.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        time::precise_time_ns() / 1000000
+    }
+}
+
+/// Clock which only moves when told to. Clones share the same time, so one
+/// can be handed over to the `Server` and another kept to drive it.
+#[derive(Clone)]
+pub struct ManualClock {
+    now: Rc<Cell<u64>>,
+}
+
+impl ManualClock {
+    pub fn new(now: u64) -> ManualClock {
+        ManualClock { now: Rc::new(Cell::new(now)) }
+    }
+
+    pub fn set(&self, now: u64) { self.now.set(now); }
+    pub fn advance(&self, by: u64) { self.now.set(self.now.get() + by); }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> u64 { self.now.get() }
+}
+
+#[cfg(test)]
+mod test {
+    pub use super::{Clock, ManualClock};
+    pub use hamcrest::{assert_that, is, not, none, equal_to};
+
+    describe! manual_clock {
+        it "shares the time between clones" {
+            let clock = ManualClock::new(1000);
+            let other = clock.clone();
+
+            other.advance(500);
+            assert_that(clock.now(), is(equal_to(1500)));
+
+            clock.set(42);
+            assert_that(other.now(), is(equal_to(42)));
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use std::ascii::AsciiExt;
+
+use server::{Server, Client};
+use message::{Message, Prefix, Reply};
+use message::error::IRCError;
+
+impl Server {
+    /// Capability negotiation, no capabilities are offered so every request
+    /// is refused.
+    pub fn cmd_cap(&self, subcommand: &str, params: &[&str], client: &Client) -> Result<(), IRCError> {
+        let upper = subcommand.to_ascii_uppercase();
+        let reply = match upper.as_str() {
+            "LS" | "LIST" => vec![upper.as_str(), ""],
+            "REQ" => vec!["NAK", params.last().map_or("", |caps| *caps)],
+            "END" => return Ok(()),
+            _ => return Err(IRCError::from(Reply::InvalidCapCmd { subcommand: subcommand })),
+        };
+
+        let target = client.reply_target();
+        let mut arguments = vec![target.as_str()];
+        arguments.extend(reply.into_iter());
+        client.send_message(&Message::build(Some(Prefix::Server(self.name.as_str())), "CAP", arguments));
+        Ok(())
+    }
+
+    /// SASL is never offered, so every attempt fails.
+    pub fn cmd_authenticate(&self, _data: &str, _client: &Client) -> Result<(), IRCError> {
+        Err(IRCError::from(Reply::SaslFail))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    pub use server::test::TestSock;
+    pub use server::*;
+    pub use hamcrest::{assert_that, is, not, none, equal_to};
+
+    describe! accepted_connection {
+        before_each {
+            let mut server = Server::new("test.local".to_string(), "TestLocal".to_string(),"42X".to_string());
+
+            let sock = Box::new(TestSock::new());
+            let token = 8;
+
+            server.accept_connection(sock.clone(), token, "127.0.0.1".to_string());
+        }
+
+        it "lists no capabilities before registration" {
+            server.process_line(token, "CAP LS 302");
+
+            assert_that(sock.data().as_str(), is(equal_to(":test.local CAP * LS :\r\n")));
+        }
+
+        it "refuses requested capabilities" {
+            server.process_line(token, "CAP REQ :sasl multi-prefix");
+
+            assert_that(sock.data().as_str(), is(equal_to(":test.local CAP * NAK :sasl multi-prefix\r\n")));
+        }
+
+        it "accepts CAP END silently" {
+            server.process_line(token, "CAP END");
+
+            assert_that(sock.data().as_str(), is(equal_to("")));
+        }
+
+        it "refuses unknown subcommands" {
+            server.process_line(token, "CAP FOO");
+
+            assert_that(sock.data().as_str(), is(equal_to(":test.local 410 * FOO :Invalid CAP command\r\n")));
+        }
+
+        it "fails AUTHENTICATE before registration" {
+            server.process_line(token, "AUTHENTICATE PLAIN");
+
+            assert_that(sock.data().as_str(), is(equal_to(":test.local 904 * :SASL authentication failed\r\n")));
+        }
+
+        it "does not hold up registration" {
+            server.process_line(token, "CAP LS");
+            server.process_line(token, "NICK test");
+            server.process_line(token, "USER guest 0 * :Ronnie Reagan");
+
+            assert_that(server.client_by_token(token).unwrap().registered(), is(equal_to(true)));
+        }
+    }
+}
@@ -1,17 +1,257 @@
+mod cap;
+mod pass;
 mod nick;
 mod user;
 mod privmsg;
+mod notice;
+mod quit;
+mod ping;
 
-use message::Message;
-use server::{Server, Client};
+use std::ascii::AsciiExt;
+
+use message::{Message, Command, Prefix, Reply};
+use message::error::IRCError;
+use server::{Server, Client, Token};
+
+/// Runs a command on behalf of the client. Only called once the checks
+/// declared in the `CommandSpec` passed.
+pub type CommandHandler = fn(&Server, &Message, &Client) -> Result<(), IRCError>;
+
+/// A command known to the server along with the requirements the
+/// dispatcher enforces before running its handler.
+#[derive(Copy)]
+pub struct CommandSpec {
+    pub handler: CommandHandler,
+    /// Fewer parameters are answered with ERR_NEEDMOREPARAMS.
+    pub min_params: usize,
+    /// Unregistered clients are answered with ERR_NOTREGISTERED.
+    pub requires_registration: bool,
+    /// Clients which are not IRC operators are answered with
+    /// ERR_NOPRIVILEGES.
+    pub oper_only: bool,
+    /// Amount of tokens the command takes out of the client's flood
+    /// control bucket.
+    pub flood_cost: u32,
+    /// Whether failures are reported to the client, NOTICE must never
+    /// trigger a reply.
+    pub reply_errors: bool,
+}
+
+// Clone is not implemented for function pointers taking references, so it
+// can't be derived.
+impl Clone for CommandSpec {
+    fn clone(&self) -> CommandSpec { *self }
+}
+
+impl CommandSpec {
+    /// A command without any requirements.
+    pub fn new(handler: CommandHandler) -> CommandSpec {
+        CommandSpec {
+            handler: handler,
+            min_params: 0,
+            requires_registration: false,
+            oper_only: false,
+            flood_cost: 1,
+            reply_errors: true,
+        }
+    }
+}
+
+/// Commands handled by the server itself. Their parameters are checked by
+/// `Command::from_message`, which knows the specific error replies.
+pub fn builtin_commands() -> Vec<(&'static str, CommandSpec)> {
+    let unregistered = CommandSpec::new(run_typed);
+    let typed = CommandSpec { requires_registration: true, ..unregistered };
+
+    vec![
+        ("CAP", unregistered),
+        ("AUTHENTICATE", unregistered),
+        ("PASS", unregistered),
+        ("NICK", CommandSpec { flood_cost: 3, ..unregistered }),
+        ("USER", unregistered),
+        ("QUIT", unregistered),
+        ("PING", unregistered),
+        ("PONG", unregistered),
+        ("PRIVMSG", typed),
+        ("NOTICE", CommandSpec { reply_errors: false, ..typed }),
+    ]
+}
+
+fn run_typed(server: &Server, message: &Message, client: &Client) -> Result<(), IRCError> {
+    match try!(Command::from_message(message)) {
+        Command::Cap { subcommand, params } => server.cmd_cap(subcommand, &params, client),
+        Command::Authenticate(data) => server.cmd_authenticate(data, client),
+        Command::Pass(password) => server.cmd_pass(password, client),
+        Command::Nick(nickname) => server.cmd_nick(nickname, client),
+        Command::User { username, realname, .. } => server.cmd_user(username, realname, client),
+        Command::Privmsg { targets, text } => server.cmd_privmsg(targets, text, client),
+        Command::Notice { targets, text } => server.cmd_notice(targets, text, client),
+        Command::Quit(reason) => server.cmd_quit(reason, client),
+        Command::Ping(origin) => server.cmd_ping(origin, client),
+        // The activity of the client is recorded for every line, so
+        // there's nothing left to do for PONG.
+        Command::Pong(_) => Ok(()),
+        _ => Ok(()),
+    }
+}
 
 impl Server {
+    /// Makes the command available to clients, replacing any previous
+    /// command of the same name.
+    pub fn register_command(&mut self, name: &str, spec: CommandSpec) {
+        self.commands.insert(name.to_ascii_uppercase(), spec);
+    }
+
+    pub fn command_spec(&self, name: &str) -> Option<&CommandSpec> {
+        self.commands.get(&name.to_ascii_uppercase())
+    }
+
+    /// Runs the handler for the message, a failure is reported back to the
+    /// client as a numeric reply.
     pub fn resolve_command(&self, message: Message, client: &Client) {
-        match message.command.to_uppercase().as_str() {
-            "NICK" => self.cmd_nick(message, client),
-            "USER" => self.cmd_user(message, client),
-            "PRIVMSG" => self.cmd_privmsg(message, client),
-            _ => ()
+        if let Err(error) = self.dispatch(&message, client) {
+            if self.command_spec(message.command).map_or(true, |spec| spec.reply_errors) {
+                self.send_error(client, error);
+            }
+        }
+    }
+
+    fn dispatch(&self, message: &Message, client: &Client) -> Result<(), IRCError> {
+        let spec = match self.command_spec(message.command) {
+            Some(spec) => *spec,
+            // Before registration everything outside of the registration
+            // commands is refused the same way.
+            None if !client.registered() => return Err(IRCError::from(Reply::NotRegistered)),
+            None => return Err(IRCError::unknown_command(message.command)),
+        };
+
+        if spec.requires_registration && !client.registered() {
+            return Err(IRCError::from(Reply::NotRegistered));
+        }
+        if spec.oper_only && !client.is_oper() {
+            return Err(IRCError::from(Reply::NoPrivileges));
+        }
+        if message.arguments.len() < spec.min_params {
+            return Err(IRCError::need_more_params(message.command.to_ascii_uppercase().as_str()));
+        }
+
+        (spec.handler)(self, message, client)
+    }
+
+    pub fn send_error(&self, client: &Client, error: IRCError) {
+        if let Some(message) = error.to_message(self.name.as_str(), client.reply_target().as_str()) {
+            client.send_message(&message.as_message());
+        }
+    }
+
+    /// Sends a NOTICE from the server, e.g. about the progress of the
+    /// connection.
+    pub fn send_notice(&self, token: Token, text: &str) {
+        if let Some(client) = self.client_by_token(token) {
+            let target = client.reply_target();
+            client.send_message(&Message::build(Some(Prefix::Server(self.name.as_str())), "NOTICE",
+                vec![target.as_str(), text]));
+        }
+    }
+
+    pub fn send_reply(&self, client: &Client, reply: Reply) {
+        let message = reply.to_message(self.name.as_str(), client.reply_target().as_str());
+        client.send_message(&message.as_message());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    pub use server::test::TestSock;
+    pub use server::*;
+    pub use message::Message;
+    pub use message::error::IRCError;
+    pub use hamcrest::{assert_that, is, not, none, equal_to};
+
+    pub fn cmd_hello(server: &Server, message: &Message, client: &Client) -> Result<(), IRCError> {
+        client.send(format!(":{} NOTICE {} :hello {}", server.name(), client.reply_target(),
+            message.arguments[0]).as_str());
+        Ok(())
+    }
+
+    describe! registry {
+        before_each {
+            let mut server = Server::new("test.local".to_string(), "TestLocal".to_string(),"42X".to_string());
+            server.register_command("hello", CommandSpec {
+                min_params: 1,
+                requires_registration: true,
+                ..CommandSpec::new(cmd_hello)
+            });
+            server.register_command("SECRET", CommandSpec {
+                requires_registration: true,
+                oper_only: true,
+                ..CommandSpec::new(cmd_hello)
+            });
+
+            let sock = Box::new(TestSock::new());
+            let token = 8;
+
+            server.accept_connection(sock.clone(), token, "127.0.0.1".to_string());
+        }
+
+        it "replies ERR_NOTREGISTERED before registration" {
+            server.process_line(token, "HELLO world");
+
+            assert_that(sock.data().as_str(), is(equal_to(":test.local 451 * :You have not registered\r\n")));
+        }
+
+        it "refuses PRIVMSG before registration" {
+            server.process_line(token, "NICK test");
+            server.process_line(token, "PRIVMSG test :hi");
+
+            assert_that(sock.data().as_str(), is(equal_to(":test.local 451 test :You have not registered\r\n")));
+        }
+
+        it "replies ERR_NOTREGISTERED to unknown commands before registration" {
+            server.process_line(token, "LIST");
+
+            assert_that(sock.data().as_str(), is(equal_to(":test.local 451 * :You have not registered\r\n")));
+        }
+
+        describe! registered_client {
+            before_each {
+                server.process_line(token, "NICK test");
+                server.process_line(token, "USER guest 0 * :Ronnie Reagan");
+                sock.clear();
+            }
+
+            it "replies ERR_UNKNOWNCOMMAND to unknown commands" {
+                server.process_line(token, "LIST");
+
+                assert_that(sock.data().as_str(), is(equal_to(":test.local 421 test LIST :Unknown command\r\n")));
+            }
+
+            it "runs an added command" {
+                server.process_line(token, "hello world");
+
+                assert_that(sock.data().as_str(), is(equal_to(":test.local NOTICE test :hello world\r\n")));
+            }
+
+            it "replies ERR_NEEDMOREPARAMS with missing parameters" {
+                server.process_line(token, "HELLO");
+
+                assert_that(sock.data().as_str(),
+                    is(equal_to(":test.local 461 test HELLO :Not enough parameters\r\n")));
+            }
+
+            it "replies ERR_NOPRIVILEGES to users" {
+                server.process_line(token, "SECRET x");
+
+                assert_that(sock.data().as_str(),
+                    is(equal_to(":test.local 481 test :Permission Denied- You're not an IRC operator\r\n")));
+            }
+
+            it "runs oper commands for opers" {
+                server.client_by_token(token).unwrap().set_oper(true);
+                server.process_line(token, "SECRET x");
+
+                assert_that(sock.data().as_str(), is(equal_to(":test.local NOTICE test :hello x\r\n")));
+            }
         }
     }
 }
@@ -1,28 +1,129 @@
 use server::{Server, Client};
-use message::Message;
+use message::Reply;
+use message::error::IRCError;
 
 impl Server {
-    pub fn cmd_nick(&self, message: Message, client: &Client) {
-        client.set_nickname(message.arguments[0].to_string());
+    /// Sets or changes the nickname. Registered clients announce the change
+    /// to themselves and everyone sharing a channel with them.
+    pub fn cmd_nick(&self, nickname: &str, client: &Client) -> Result<(), IRCError> {
+        if !is_nickname_valid(nickname, self.config.nicklen) {
+            return Err(IRCError::from(Reply::ErroneusNickname { nick: nickname }));
+        }
+        if let Some(other) = self.client_by_nickname(&nickname.to_string()) {
+            // Changing the case of one's own nickname is fine.
+            if other.token != client.token {
+                return Err(IRCError::from(Reply::NicknameInUse { nick: nickname }));
+            }
+        }
+        if client.nickname().as_ref().map(|n| n.as_str()) == Some(nickname) {
+            return Ok(());
+        }
+
+        let prefix = client.prefix();
+        self.change_nickname(client, nickname);
+
         if client.registered() {
-            self.register_client(client);
+            let line = format!(":{} NICK {}", prefix, nickname);
+            client.send(line.as_str());
+            for peer in self.peers_of(client).into_iter() {
+                if let Some(peer) = self.client_by_token(peer) {
+                    peer.send(line.as_str());
+                }
+            }
+        } else {
+            self.try_register(client);
         }
+        Ok(())
     }
 }
 
+///    <nick>       ::= <first> { <letter> | <number> | <special> | '-' }
+///    <first>      ::= <letter> | <special>
+///    <letter>     ::= 'a' ... 'z' | 'A' ... 'Z'
+///    <number>     ::= '0' ... '9'
+///    <special>    ::= '[' | ']' | '\' | '`' | '_' | '^' | '{' | '|' | '}'
+const SPECIAL_CHARS: &'static str = "[]\\`_^{|}";
+
+pub fn is_nickname_valid(nickname: &str, max_length: usize) -> bool {
+    if nickname.len() == 0 || nickname.len() > max_length {
+        return false;
+    }
+
+    nickname.chars().enumerate().all(|(i, c)| match c {
+        'a'...'z' | 'A'...'Z' => true,
+        '0'...'9' | '-' => i > 0,
+        _ => SPECIAL_CHARS.contains(c),
+    })
+}
+
 #[cfg(test)]
 mod test {
+    pub use server::test::TestSock;
     pub use server::*;
+    pub use super::is_nickname_valid;
     pub use hamcrest::{assert_that, is, not, none, equal_to};
 
+    describe! nickname_validation {
+        it "accepts letters, digits and specials" {
+            assert_that(is_nickname_valid("Wiz", 9), is(equal_to(true)));
+            assert_that(is_nickname_valid("[away]_2-x", 30), is(equal_to(true)));
+        }
+
+        it "rejects a leading digit or dash" {
+            assert_that(is_nickname_valid("2wiz", 9), is(equal_to(false)));
+            assert_that(is_nickname_valid("-wiz", 9), is(equal_to(false)));
+        }
+
+        it "rejects other characters" {
+            assert_that(is_nickname_valid("$%", 9), is(equal_to(false)));
+            assert_that(is_nickname_valid("wiz!", 9), is(equal_to(false)));
+            assert_that(is_nickname_valid("wïz", 9), is(equal_to(false)));
+        }
+
+        it "enforces the length limit" {
+            assert_that(is_nickname_valid("abcdefghi", 9), is(equal_to(true)));
+            assert_that(is_nickname_valid("abcdefghij", 9), is(equal_to(false)));
+        }
+    }
+
     describe! accepted_connection {
         before_each {
-            let mut server = Server::new("test.local".to_string(), "TestLocal".to_string(),"42X".to_string());
+            let mut config = Config::default();
+            config.nicklen = 9;
+            let mut server = Server::with_config("test.local".to_string(), "TestLocal".to_string(),
+                "42X".to_string(), config);
 
-            let sock = Box::new(Vec::new());
+            let sock = Box::new(TestSock::new());
             let token = 8;
 
-            server.accept_connection(sock, token, "127.0.0.1".to_string());
+            server.accept_connection(sock.clone(), token, "127.0.0.1".to_string());
+        }
+
+        it "fails with ERR_NONICKNAMEGIVEN when no nickname is passed" {
+            server.process_line(token, "NICK");
+
+            assert_that(sock.data().as_str(), is(equal_to(":test.local 431 * :No nickname given\r\n")));
+            assert_that(server.client_by_token(8).unwrap().nickname(), is(none()));
+        }
+
+        it "fails with ERR_ERRONEUSNICKNAME for an invalid nickname" {
+            server.process_line(token, "NICK $%");
+
+            assert_that(sock.data().as_str(), is(equal_to(":test.local 432 * $% :Erroneus nickname\r\n")));
+            assert_that(server.client_by_token(8).unwrap().nickname(), is(none()));
+        }
+
+        it "does not send a reply which would not parse back" {
+            server.process_line(token, "NICK ::x");
+
+            assert_that(sock.data().as_str(), is(equal_to("")));
+        }
+
+        it "fails with ERR_ERRONEUSNICKNAME for an overly long nickname" {
+            server.process_line(token, "NICK abcdefghij");
+
+            assert_that(sock.data().as_str(),
+                is(equal_to(":test.local 432 * abcdefghij :Erroneus nickname\r\n")));
         }
 
         it "sets user nick when NICK is passed" {
@@ -40,7 +141,77 @@ mod test {
             let user = server.client_by_token(8).unwrap();
 
             assert_that(user.nickname().unwrap().as_str(), is(equal_to("test2")));
+            assert_that(server.client_by_nickname(&"test".to_string()), is(none()));
         }
-    }
 
+        describe! with_peer {
+            before_each {
+                let peer_sock = Box::new(TestSock::new());
+                let peer_token = 9;
+
+                server.accept_connection(peer_sock.clone(), peer_token, "127.0.0.2".to_string());
+                server.process_line(peer_token, "NICK peer");
+                server.process_line(peer_token, "USER peer 0 * :Peer");
+                peer_sock.clear();
+            }
+
+            it "fails with ERR_NICKNAMEINUSE for a taken nickname" {
+                server.process_line(token, "NICK Peer");
+
+                assert_that(sock.data().as_str(),
+                    is(equal_to(":test.local 433 * Peer :Nickname is already in use\r\n")));
+                assert_that(server.client_by_token(8).unwrap().nickname(), is(none()));
+            }
+
+            it "lets a client change the case of its nickname" {
+                server.process_line(peer_token, "NICK PEER");
+
+                assert_that(peer_sock.data().as_str(), is(equal_to(":peer!~peer@127.0.0.2 NICK PEER\r\n")));
+                assert_that(server.client_by_token(peer_token).unwrap().nickname(),
+                    is(equal_to(Some("PEER".to_string()))));
+            }
+
+            describe! registered_client {
+                before_each {
+                    server.process_line(token, "NICK test");
+                    server.process_line(token, "USER guest 0 * :Ronnie Reagan");
+                    sock.clear();
+                }
+
+                it "updates the nickname index" {
+                    server.process_line(token, "NICK other");
+
+                    assert_that(server.client_by_nickname(&"test".to_string()), is(none()));
+                    assert_that(server.client_by_nickname(&"other".to_string()).unwrap().token,
+                        is(equal_to(token)));
+                }
+
+                it "notifies the user of the change" {
+                    server.process_line(token, "NICK other");
+
+                    assert_that(sock.data().as_str(), is(equal_to(":test!~guest@127.0.0.1 NICK other\r\n")));
+                }
+
+                it "ignores a change to the same nickname" {
+                    server.process_line(token, "NICK test");
+
+                    assert_that(sock.data().as_str(), is(equal_to("")));
+                }
+
+                it "notifies clients sharing a channel" {
+                    server.join_channel("#test", server.client_by_token(token).unwrap());
+                    server.join_channel("#test", server.client_by_token(peer_token).unwrap());
+                    server.process_line(token, "NICK other");
+
+                    assert_that(peer_sock.data().as_str(), is(equal_to(":test!~guest@127.0.0.1 NICK other\r\n")));
+                }
+
+                it "does not notify unrelated clients" {
+                    server.process_line(token, "NICK other");
+
+                    assert_that(peer_sock.data().as_str(), is(equal_to("")));
+                }
+            }
+        }
+    }
 }
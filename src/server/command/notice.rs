@@ -0,0 +1,91 @@
+use server::{Server, Client};
+use message::error::IRCError;
+
+impl Server {
+    /// Delivers like PRIVMSG, but failures are never replied to.
+    pub fn cmd_notice(&self, targets: Vec<&str>, text: &str, client: &Client) -> Result<(), IRCError> {
+        self.deliver_text("NOTICE", targets, text, client);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    pub use server::test::TestSock;
+    pub use server::*;
+    pub use hamcrest::{assert_that, is, not, none, equal_to};
+
+    describe! accepted_connection {
+        before_each {
+            let mut server = Server::new("test.local".to_string(), "TestLocal".to_string(),"42X".to_string());
+
+            let sock = Box::new(TestSock::new());
+            let token = 8;
+
+            server.accept_connection(sock.clone(), token, "127.0.0.1".to_string());
+        }
+
+        it "does not reply before registration" {
+            server.process_line(token, "NOTICE test :hi");
+
+            assert_that(sock.data().as_str(), is(equal_to("")));
+        }
+
+        it "sends server notices" {
+            server.send_notice(token, "*** Using your IP address as hostname");
+
+            assert_that(sock.data().as_str(),
+                is(equal_to(":test.local NOTICE * :*** Using your IP address as hostname\r\n")));
+        }
+
+        describe! registered_client {
+            before_each {
+                server.process_line(token, "NICK test");
+                server.process_line(token, "USER guest 0 * :Ronnie Reagan");
+                sock.clear();
+
+                let peer_sock = Box::new(TestSock::new());
+                let peer_token = 9;
+
+                server.accept_connection(peer_sock.clone(), peer_token, "127.0.0.2".to_string());
+                server.process_line(peer_token, "NICK peer");
+                server.process_line(peer_token, "USER peer 0 * :Peer");
+                peer_sock.clear();
+            }
+
+            it "delivers to users" {
+                server.process_line(token, "NOTICE peer,test :hello there");
+
+                assert_that(peer_sock.data().as_str(),
+                    is(equal_to(":test!~guest@127.0.0.1 NOTICE peer :hello there\r\n")));
+                assert_that(sock.data().as_str(),
+                    is(equal_to(":test!~guest@127.0.0.1 NOTICE test :hello there\r\n")));
+            }
+
+            it "delivers to channels" {
+                server.join_channel("#test", server.client_by_token(token).unwrap());
+                server.join_channel("#test", server.client_by_token(peer_token).unwrap());
+                server.process_line(token, "NOTICE #test :hello there");
+
+                assert_that(peer_sock.data().as_str(),
+                    is(equal_to(":test!~guest@127.0.0.1 NOTICE #test :hello there\r\n")));
+            }
+
+            it "addresses server notices to the nickname" {
+                server.send_notice(token, "Hello");
+
+                assert_that(sock.data().as_str(), is(equal_to(":test.local NOTICE test Hello\r\n")));
+            }
+
+            it "never replies with errors" {
+                server.process_line(token, "NOTICE nobody :hi");
+                server.process_line(token, "NOTICE #nowhere :hi");
+                server.process_line(token, "NOTICE a,b,c,d,e :hi");
+                server.process_line(token, "NOTICE peer");
+                server.process_line(token, "NOTICE");
+
+                assert_that(sock.data().as_str(), is(equal_to("")));
+            }
+        }
+    }
+}
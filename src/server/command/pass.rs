@@ -0,0 +1,123 @@
+use server::{Server, Client};
+use message::Reply;
+use message::error::IRCError;
+
+impl Server {
+    /// Keeps the password for the check done at registration, the last one sent wins.
+    pub fn cmd_pass(&self, password: &str, client: &Client) -> Result<(), IRCError> {
+        if client.registered() {
+            return Err(IRCError::from(Reply::AlreadyRegistred));
+        }
+        *client.password.borrow_mut() = Some(password.to_string());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    pub use server::test::TestSock;
+    pub use server::*;
+    pub use hamcrest::{assert_that, is, not, none, equal_to};
+
+    describe! accepted_connection {
+        before_each {
+            let mut server = Server::new("test.local".to_string(), "TestLocal".to_string(),"42X".to_string());
+
+            let sock = Box::new(TestSock::new());
+            let token = 8;
+
+            server.accept_connection(sock.clone(), token, "127.0.0.1".to_string());
+        }
+
+        it "accepts PASS before registration" {
+            server.process_line(token, "PASS secret");
+
+            assert_that(sock.data().as_str(), is(equal_to("")));
+        }
+
+        it "fails PASS without a password" {
+            server.process_line(token, "PASS");
+
+            assert_that(sock.data().as_str(),
+                is(equal_to(":test.local 461 * PASS :Not enough parameters\r\n")));
+        }
+
+        it "fails with ERR_ALREADYREGISTRED after registration" {
+            server.process_line(token, "NICK test");
+            server.process_line(token, "USER guest 0 * :Ronnie Reagan");
+            sock.clear();
+            server.process_line(token, "PASS secret");
+
+            assert_that(sock.data().as_str(),
+                is(equal_to(":test.local 462 test :You may not reregister\r\n")));
+        }
+    }
+
+    describe! password_class {
+        before_each {
+            let mut config = Config::default();
+            config.classes.push(ClientClass {
+                name: "remote".to_string(),
+                hosts: vec!["10.*".to_string()],
+                password: None,
+            });
+            config.classes.push(ClientClass {
+                name: "local".to_string(),
+                hosts: vec!["127.*".to_string()],
+                password: Secret::parse(
+                    "sha256:NaCl:5cca3fffb01b0faeb9f287e0ac91027494c458e565c5716df9923aea47947189"),
+            });
+            let mut server = Server::with_config("test.local".to_string(), "TestLocal".to_string(),
+                "42X".to_string(), config);
+
+            let sock = Box::new(TestSock::new());
+            let token = 8;
+
+            server.accept_connection(sock.clone(), token, "127.0.0.1".to_string());
+        }
+
+        it "registers with the right password" {
+            server.process_line(token, "PASS hunter2");
+            server.process_line(token, "NICK test");
+            server.process_line(token, "USER guest 0 * :Ronnie Reagan");
+
+            assert_that(server.client_by_token(token).unwrap().registered(), is(equal_to(true)));
+        }
+
+        it "only uses the last password sent" {
+            server.process_line(token, "PASS wrong");
+            server.process_line(token, "PASS hunter2");
+            server.process_line(token, "NICK test");
+            server.process_line(token, "USER guest 0 * :Ronnie Reagan");
+
+            assert_that(server.client_by_token(token).unwrap().registered(), is(equal_to(true)));
+        }
+
+        it "drops a client with the wrong password" {
+            server.process_line(token, "PASS hunter3");
+            server.process_line(token, "NICK test");
+            server.process_line(token, "USER guest 0 * :Ronnie Reagan");
+
+            assert_that(server.client_by_token(token), is(none()));
+            assert_that(sock.data().as_str(), is(equal_to(
+                ":test.local 464 test :Password incorrect\r\n\
+                 ERROR :Closing Link: 127.0.0.1 (Bad Password)\r\n")));
+        }
+
+        it "drops a client without a password" {
+            server.process_line(token, "NICK test");
+            server.process_line(token, "USER guest 0 * :Ronnie Reagan");
+
+            assert_that(server.client_by_token(token), is(none()));
+        }
+
+        it "lets clients of other classes in without a password" {
+            let other_sock = Box::new(TestSock::new());
+            server.accept_connection(other_sock.clone(), 9, "10.0.0.1".to_string());
+            server.process_line(9, "NICK other");
+            server.process_line(9, "USER guest 0 * :Ronnie Reagan");
+
+            assert_that(server.client_by_token(9).unwrap().registered(), is(equal_to(true)));
+        }
+    }
+}
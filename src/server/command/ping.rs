@@ -0,0 +1,55 @@
+use server::{Server, Client};
+use message::error::IRCError;
+
+impl Server {
+    pub fn cmd_ping(&self, origin: &str, client: &Client) -> Result<(), IRCError> {
+        client.send(format!(":{} PONG {} :{}", self.name, self.name, origin).as_str());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    pub use server::test::TestSock;
+    pub use server::*;
+    pub use hamcrest::{assert_that, is, not, none, equal_to};
+
+    describe! accepted_connection {
+        before_each {
+            let mut server = Server::new("test.local".to_string(), "TestLocal".to_string(),"42X".to_string());
+
+            let sock = Box::new(TestSock::new());
+            let token = 8;
+
+            server.accept_connection(sock.clone(), token, "127.0.0.1".to_string());
+        }
+
+        it "answers PING with PONG" {
+            server.process_line(token, "PING :12345");
+
+            assert_that(sock.data().as_str(),
+                is(equal_to(":test.local PONG test.local :12345\r\n")));
+        }
+
+        it "fails PING without origin" {
+            server.process_line(token, "PING");
+
+            assert_that(sock.data().as_str(),
+                is(equal_to(":test.local 409 * :No origin specified\r\n")));
+        }
+
+        it "silently accepts PONG" {
+            server.process_line(token, "PONG :test.local");
+
+            assert_that(sock.data().as_str(), is(equal_to("")));
+        }
+
+        it "fails PONG without origin" {
+            server.process_line(token, "NICK test");
+            server.process_line(token, "PONG");
+
+            assert_that(sock.data().as_str(),
+                is(equal_to(":test.local 409 test :No origin specified\r\n")));
+        }
+    }
+}
@@ -1,16 +1,84 @@
+use std::collections::HashSet;
+
 use server::{Server, Client};
-use message::Message;
+use server::channel::is_channel_name;
+use message::{Message, Prefix, Reply};
+use message::error::IRCError;
 
 impl Server {
-    pub fn cmd_privmsg(&self, message: Message, client: &Client) {
-        let target = message.arguments[0];
-        let text = message.arguments[1];
+    /// Delivers the text to every target, the ones which cannot be reached
+    /// are reported without holding up the others.
+    pub fn cmd_privmsg(&self, targets: Vec<&str>, text: &str, client: &Client) -> Result<(), IRCError> {
+        for error in self.deliver_text("PRIVMSG", targets, text, client).into_iter() {
+            self.send_error(client, error);
+        }
+        Ok(())
+    }
+
+    /// Sends the text to users and channels as PRIVMSG or NOTICE, returning
+    /// the errors for the targets which could not be reached. Repeated
+    /// targets get the text once. The first target over MAXTARGETS is
+    /// reported and the rest of the list is dropped.
+    pub fn deliver_text(&self, command: &str, targets: Vec<&str>, text: &str, client: &Client) -> Vec<IRCError> {
+        let mut seen = HashSet::new();
+        let mut errors = Vec::new();
+
+        for target in targets.into_iter() {
+            if !seen.insert(self.config.casemapping.fold(target)) {
+                continue;
+            }
+
+            if seen.len() > self.config.max_targets {
+                errors.push(IRCError::from(Reply::TooManyTargets { target: target }));
+                break;
+            }
+
+            let result = if is_channel_name(target) {
+                self.deliver_to_channel(command, target, text, client)
+            } else {
+                self.deliver_to_user(command, target, text, client)
+            };
+
+            if let Err(error) = result {
+                errors.push(error);
+            }
+        }
+        errors
+    }
+
+    fn deliver_to_user(&self, command: &str, target: &str, text: &str, client: &Client) -> Result<(), IRCError> {
+        let target_client = match self.client_by_nickname(&target.to_string()) {
+            Some(target_client) if target_client.registered() => target_client,
+            _ => return Err(IRCError::no_such_nick(target)),
+        };
 
-        let target_client = self.client_by_nickname(&target.to_string()).unwrap();
         let src_prefix = client.prefix();
-        let msg = Message::build(Some(src_prefix.as_str()), "PRIVMSG", vec!(target, text));
+        let msg = Message::build(Some(Prefix::parse(src_prefix.as_str())), command, vec!(target, text));
+        target_client.send_message(&msg);
+        Ok(())
+    }
+
+    /// Sends the text to every member but the sender, which has to be on
+    /// the channel itself.
+    fn deliver_to_channel(&self, command: &str, target: &str, text: &str, client: &Client) -> Result<(), IRCError> {
+        let key = self.config.casemapping.fold(target);
+        let (name, members) = match self.channels.borrow().get(&key) {
+            Some(channel) => (channel.name().clone(), channel.members()),
+            None => return Err(IRCError::no_such_nick(target)),
+        };
+        if !members.contains(&client.token) {
+            return Err(IRCError::from(Reply::CannotSendToChan { channel: name.as_str() }));
+        }
 
-        write!(target_client.out_socket.borrow_mut(), "{:?}\r\n", msg);
+        let src_prefix = client.prefix();
+        let msg = Message::build(Some(Prefix::parse(src_prefix.as_str())), command,
+            vec!(name.as_str(), text));
+        for token in members.into_iter().filter(|token| *token != client.token) {
+            if let Some(member) = self.client_by_token(token) {
+                member.send_message(&msg);
+            }
+        }
+        Ok(())
     }
 }
 
@@ -41,7 +109,122 @@ mod test {
                 server.process_line(token, "PRIVMSG test test");
 
                 assert_that(sock.data().as_str(),
-                    is(equal_to(":test!guest@127.0.0.1 PRIVMSG test test\r\n")));
+                    is(equal_to(":test!~guest@127.0.0.1 PRIVMSG test test\r\n")));
+            }
+
+            it "keeps texts starting with a colon intact" {
+                server.process_line(token, "PRIVMSG test ::-)");
+
+                assert_that(sock.data().as_str(),
+                    is(equal_to(":test!~guest@127.0.0.1 PRIVMSG test ::-)\r\n")));
+            }
+
+            it "keeps texts with spaces intact" {
+                server.process_line(token, "PRIVMSG test :hello there");
+
+                assert_that(sock.data().as_str(),
+                    is(equal_to(":test!~guest@127.0.0.1 PRIVMSG test :hello there\r\n")));
+            }
+
+            it "finds the target regardless of case" {
+                server.process_line(token, "PRIVMSG TEST :hi");
+
+                assert_that(sock.data().as_str(),
+                    is(equal_to(":test!~guest@127.0.0.1 PRIVMSG TEST hi\r\n")));
+            }
+
+            it "fails with ERR_NOSUCHNICK for an unknown target" {
+                server.process_line(token, "PRIVMSG nobody :hi");
+
+                assert_that(sock.data().as_str(),
+                    is(equal_to(":test.local 401 test nobody :No such nick/channel\r\n")));
+            }
+
+            it "still delivers to the known targets" {
+                server.process_line(token, "PRIVMSG nobody,test :hi");
+
+                assert_that(sock.data().as_str(), is(equal_to(
+                    ":test!~guest@127.0.0.1 PRIVMSG test hi\r\n\
+                     :test.local 401 test nobody :No such nick/channel\r\n")));
+            }
+
+            it "fails with ERR_NOSUCHNICK for an unregistered target" {
+                let other_sock = Box::new(TestSock::new());
+                server.accept_connection(other_sock.clone(), 10, "127.0.0.3".to_string());
+                server.process_line(10, "NICK other");
+                server.process_line(token, "PRIVMSG other :hi");
+
+                assert_that(sock.data().as_str(),
+                    is(equal_to(":test.local 401 test other :No such nick/channel\r\n")));
+                assert_that(other_sock.data().as_str(), is(equal_to("")));
+            }
+
+            it "delivers once to a repeated target" {
+                server.process_line(token, "PRIVMSG test,TEST :hi");
+
+                assert_that(sock.data().as_str(), is(equal_to(":test!~guest@127.0.0.1 PRIVMSG test hi\r\n")));
+            }
+
+            it "fails with a single ERR_TOOMANYTARGETS over MAXTARGETS" {
+                server.process_line(token, "PRIVMSG a,b,c,test,e,f,g :hi");
+
+                assert_that(sock.data().as_str(), is(equal_to(
+                    ":test!~guest@127.0.0.1 PRIVMSG test hi\r\n\
+                     :test.local 401 test a :No such nick/channel\r\n\
+                     :test.local 401 test b :No such nick/channel\r\n\
+                     :test.local 401 test c :No such nick/channel\r\n\
+                     :test.local 407 test e :Too many targets. Message not delivered\r\n")));
+            }
+
+            it "fails with ERR_NOSUCHNICK for an unknown channel" {
+                server.process_line(token, "PRIVMSG #nowhere :hi");
+
+                assert_that(sock.data().as_str(),
+                    is(equal_to(":test.local 401 test #nowhere :No such nick/channel\r\n")));
+            }
+
+            describe! with_channel {
+                before_each {
+                    let peer_sock = Box::new(TestSock::new());
+                    let peer_token = 9;
+
+                    server.accept_connection(peer_sock.clone(), peer_token, "127.0.0.2".to_string());
+                    server.process_line(peer_token, "NICK peer");
+                    server.process_line(peer_token, "USER peer 0 * :Peer");
+                    server.join_channel("#Test", server.client_by_token(peer_token).unwrap());
+                    peer_sock.clear();
+                }
+
+                it "delivers to the other members" {
+                    server.join_channel("#Test", server.client_by_token(token).unwrap());
+                    server.process_line(token, "PRIVMSG #test :hi all");
+
+                    assert_that(peer_sock.data().as_str(),
+                        is(equal_to(":test!~guest@127.0.0.1 PRIVMSG #Test :hi all\r\n")));
+                    assert_that(sock.data().as_str(), is(equal_to("")));
+                }
+
+                it "fails with ERR_CANNOTSENDTOCHAN for outsiders" {
+                    server.process_line(token, "PRIVMSG #test :hi all");
+
+                    assert_that(sock.data().as_str(),
+                        is(equal_to(":test.local 404 test #Test :Cannot send to channel\r\n")));
+                    assert_that(peer_sock.data().as_str(), is(equal_to("")));
+                }
+            }
+
+            it "fails with ERR_NORECIPIENT without a target" {
+                server.process_line(token, "PRIVMSG");
+
+                assert_that(sock.data().as_str(),
+                    is(equal_to(":test.local 411 test :No recipient given (PRIVMSG)\r\n")));
+            }
+
+            it "fails with ERR_NOTEXTTOSEND without a text" {
+                server.process_line(token, "PRIVMSG test");
+
+                assert_that(sock.data().as_str(),
+                    is(equal_to(":test.local 412 test :No text to send\r\n")));
             }
         }
     }
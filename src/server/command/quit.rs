@@ -0,0 +1,90 @@
+use server::{Server, Client};
+use message::error::IRCError;
+
+impl Server {
+    pub fn cmd_quit(&self, reason: Option<&str>, client: &Client) -> Result<(), IRCError> {
+        let reason = match reason {
+            Some(reason) => format!("Quit: {}", reason),
+            None => "Client Quit".to_string(),
+        };
+        client.exit(reason.as_str());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    pub use server::test::TestSock;
+    pub use server::*;
+    pub use hamcrest::{assert_that, is, not, none, equal_to};
+
+    describe! accepted_connection {
+        before_each {
+            let mut server = Server::new("test.local".to_string(), "TestLocal".to_string(),"42X".to_string());
+
+            let sock = Box::new(TestSock::new());
+            let token = 8;
+
+            server.accept_connection(sock.clone(), token, "127.0.0.1".to_string());
+            server.process_line(token, "NICK test");
+            server.process_line(token, "USER guest 0 * :Ronnie Reagan");
+            sock.clear();
+        }
+
+        it "removes the client upon QUIT" {
+            server.process_line(token, "QUIT");
+
+            assert_that(server.client_by_token(8), is(none()));
+            assert_that(server.client_by_nickname(&"test".to_string()), is(none()));
+            assert_that(server.drain_closed(), is(equal_to(vec![8])));
+        }
+
+        it "sends ERROR with the quit reason to the leaver" {
+            server.process_line(token, "QUIT :gone fishing");
+
+            assert_that(sock.data().as_str(),
+                is(equal_to("ERROR :Closing Link: 127.0.0.1 (Quit: gone fishing)\r\n")));
+        }
+
+        it "uses a default reason when none is given" {
+            server.process_line(token, "QUIT");
+
+            assert_that(sock.data().as_str(),
+                is(equal_to("ERROR :Closing Link: 127.0.0.1 (Client Quit)\r\n")));
+        }
+
+        it "ignores lines following QUIT" {
+            server.process_data(token, b"QUIT\r\nNICK other\r\n");
+
+            assert_that(server.client_by_token(8), is(none()));
+        }
+
+        describe! with_peer {
+            before_each {
+                let peer_sock = Box::new(TestSock::new());
+                let peer_token = 9;
+
+                server.accept_connection(peer_sock.clone(), peer_token, "127.0.0.2".to_string());
+                server.process_line(peer_token, "NICK peer");
+                server.process_line(peer_token, "USER peer 0 * :Peer");
+                peer_sock.clear();
+            }
+
+            it "notifies clients sharing a channel" {
+                server.join_channel("#test", server.client_by_token(token).unwrap());
+                server.join_channel("#test", server.client_by_token(peer_token).unwrap());
+                server.process_line(token, "QUIT :bye");
+
+                assert_that(peer_sock.data().as_str(),
+                    is(equal_to(":test!~guest@127.0.0.1 QUIT :Quit: bye\r\n")));
+            }
+
+            it "does not notify unrelated clients" {
+                server.process_line(token, "QUIT :bye");
+
+                assert_that(peer_sock.data().as_str(), is(equal_to("")));
+            }
+        }
+    }
+
+}
@@ -1,30 +1,88 @@
 use server::{Server, Client};
-use message::Message;
+use message::Reply;
+use message::error::IRCError;
 
 impl Server {
-    pub fn cmd_user(&self, message: Message, client: &Client) {
-        client.set_username(message.arguments[0].to_string());
-        client.set_realname(message.arguments[3].to_string());
-
+    /// Unless ident confirmed the username, it gets prefixed with `~`. The
+    /// username is cut to fit USERLEN and the realname to fit REALLEN.
+    pub fn cmd_user(&self, username: &str, realname: &str, client: &Client) -> Result<(), IRCError> {
         if client.registered() {
-            self.register_client(client);
+            return Err(IRCError::from(Reply::AlreadyRegistred));
+        }
+        if !is_username_valid(username) {
+            client.exit(format!("Invalid username [{}]", username).as_str());
+            return Ok(());
         }
+
+        // An ident reply is only trusted if it would be a valid username.
+        let username = match client.ident.borrow().clone() {
+            Some(ref ident) if is_username_valid(ident) =>
+                truncate(ident.as_str(), self.config.userlen).to_string(),
+            _ => format!("~{}", truncate(username, self.config.userlen.saturating_sub(1))),
+        };
+        client.set_username(username);
+        client.set_realname(truncate(realname, self.config.reallen).to_string());
+        self.try_register(client);
+        Ok(())
     }
 }
 
+///    <user>       ::= <first> { <letter> | <number> | <special> }
+///    <first>      ::= <letter> | <number>
+///    <special>    ::= '-' | '_' | '.' | '[' | ']' | '\' | '`' | '^' | '{' | '|' | '}'
+const SPECIAL_CHARS: &'static str = "-_.[]\\`^{|}";
+
+pub fn is_username_valid(username: &str) -> bool {
+    username.len() > 0 && username.chars().enumerate().all(|(i, c)| match c {
+        'a'...'z' | 'A'...'Z' | '0'...'9' => true,
+        _ => i > 0 && SPECIAL_CHARS.contains(c),
+    })
+}
+
+/// Cuts the text to at most `max_length` bytes, on a character boundary.
+fn truncate(text: &str, max_length: usize) -> &str {
+    let mut end = 0;
+    for (i, c) in text.char_indices() {
+        if i + c.len_utf8() > max_length {
+            break;
+        }
+        end = i + c.len_utf8();
+    }
+    &text[..end]
+}
+
 #[cfg(test)]
 mod test {
+    pub use server::test::TestSock;
     pub use server::*;
+    pub use super::is_username_valid;
     pub use hamcrest::{assert_that, is, not, none, equal_to};
 
+    describe! username_validation {
+        it "accepts letters, digits and specials" {
+            assert_that(is_username_valid("guest"), is(equal_to(true)));
+            assert_that(is_username_valid("0x.g-u_e[s]t"), is(equal_to(true)));
+        }
+
+        it "rejects a leading special" {
+            assert_that(is_username_valid("-guest"), is(equal_to(false)));
+            assert_that(is_username_valid("~guest"), is(equal_to(false)));
+        }
+
+        it "rejects other characters" {
+            assert_that(is_username_valid("gu@st"), is(equal_to(false)));
+            assert_that(is_username_valid("gü"), is(equal_to(false)));
+        }
+    }
+
     describe! accepted_connection {
         before_each {
             let mut server = Server::new("test.local".to_string(), "TestLocal".to_string(),"42X".to_string());
 
-            let sock = Box::new(Vec::new());
+            let sock = Box::new(TestSock::new());
             let token = 8;
 
-            server.accept_connection(sock, token, "127.0.0.1".to_string());
+            server.accept_connection(sock.clone(), token, "127.0.0.1".to_string());
         }
 
         it "sets user name and full name when USER is passed" {
@@ -32,9 +90,70 @@ mod test {
 
             let user = server.client_by_token(8).unwrap();
 
-            assert_that(user.username().unwrap().as_str(), is(equal_to("guest")));
+            assert_that(user.username().unwrap().as_str(), is(equal_to("~guest")));
             assert_that(user.realname().unwrap().as_str(), is(equal_to("Ronnie Reagan")));
         }
-    }
 
+        it "uses the username confirmed by ident" {
+            server.set_ident(token, "rreagan".to_string());
+            server.process_line(token, "USER guest 0 * :Ronnie Reagan");
+
+            let user = server.client_by_token(8).unwrap();
+
+            assert_that(user.username().unwrap().as_str(), is(equal_to("rreagan")));
+        }
+
+        it "ignores an invalid ident" {
+            server.set_ident(token, "r@ea gan!".to_string());
+            server.process_line(token, "USER guest 0 * :Ronnie Reagan");
+
+            let user = server.client_by_token(8).unwrap();
+
+            assert_that(user.username().unwrap().as_str(), is(equal_to("~guest")));
+        }
+
+        it "cuts the username to USERLEN" {
+            server.process_line(token, "USER abcdefghijklm 0 * :Ronnie Reagan");
+
+            let user = server.client_by_token(8).unwrap();
+
+            assert_that(user.username().unwrap().as_str(), is(equal_to("~abcdefghi")));
+        }
+
+        it "cuts the realname to REALLEN" {
+            server.process_line(token, "USER guest 0 * :Ronnie Reagan Ronnie Reagan Ronnie Reagan Ronnie Reagan");
+
+            let user = server.client_by_token(8).unwrap();
+
+            assert_that(user.realname().unwrap().as_str(),
+                is(equal_to("Ronnie Reagan Ronnie Reagan Ronnie Reagan Ronnie R")));
+        }
+
+        it "drops a client with an invalid username" {
+            server.process_line(token, "USER gu@st 0 * :Ronnie Reagan");
+
+            assert_that(server.client_by_token(8), is(none()));
+            assert_that(sock.data().as_str(),
+                is(equal_to("ERROR :Closing Link: 127.0.0.1 (Invalid username [gu@st])\r\n")));
+        }
+
+        it "fails with ERR_NEEDMOREPARAMS when parameters are missing" {
+            server.process_line(token, "USER guest 0 *");
+
+            assert_that(sock.data().as_str(),
+                is(equal_to(":test.local 461 * USER :Not enough parameters\r\n")));
+            assert_that(server.client_by_token(8).unwrap().username(), is(none()));
+        }
+
+        it "fails with ERR_ALREADYREGISTRED after registration" {
+            server.process_line(token, "NICK test");
+            server.process_line(token, "USER guest 0 * :Ronnie Reagan");
+            sock.clear();
+            server.process_line(token, "USER other 0 * :Someone Else");
+
+            assert_that(sock.data().as_str(),
+                is(equal_to(":test.local 462 test :You may not reregister\r\n")));
+            assert_that(server.client_by_token(8).unwrap().username().unwrap().as_str(), is(equal_to("~guest")));
+        }
+    }
 }
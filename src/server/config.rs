@@ -0,0 +1,69 @@
+use server::casemapping::CaseMapping;
+use server::password::ClientClass;
+
+/// Tunables of a `Server`. Use `Config::default()` and override the fields
+/// as needed.
+pub struct Config {
+    /// Version string sent with RPL_YOURHOST and RPL_MYINFO.
+    pub version: String,
+    /// Date sent with RPL_CREATED, the time the server was set up if none
+    /// is given.
+    pub created: Option<String>,
+    /// Lines of the message of the day, clients are sent ERR_NOMOTD if
+    /// there is none.
+    pub motd: Option<Vec<String>>,
+    /// Maximum amount of bytes queued for a client before it is dropped with
+    /// "Max SendQ exceeded".
+    pub max_sendq: usize,
+    /// Amount of commands a client can send in a burst before being dropped
+    /// with "Excess Flood".
+    pub flood_burst: u32,
+    /// Time it takes to regain one command in the burst, in milliseconds.
+    pub flood_refill_ms: u64,
+    /// Whether IRC operators are exempt from flood control.
+    pub flood_exempt_opers: bool,
+    /// Idle time after which a registered client is sent a PING, in
+    /// milliseconds. A client which stays silent for as long again is
+    /// dropped with "Ping timeout".
+    pub ping_frequency_ms: u64,
+    /// Time a client has to complete the registration, in milliseconds.
+    pub registration_timeout_ms: u64,
+    /// Maximum length of a nickname, advertised as NICKLEN.
+    pub nicklen: usize,
+    /// Maximum length of a username, including the `~` marking usernames
+    /// not confirmed by ident. Advertised as USERLEN.
+    pub userlen: usize,
+    /// Maximum length of a realname, longer ones are cut. Advertised as
+    /// REALLEN.
+    pub reallen: usize,
+    /// Maximum amount of targets of a single PRIVMSG, the ones over it are
+    /// answered with ERR_TOOMANYTARGETS. Advertised as MAXTARGETS.
+    pub max_targets: usize,
+    /// Rules for comparing nicknames and channel names.
+    pub casemapping: CaseMapping,
+    /// Connection classes, a client belongs to the first one matching its
+    /// hostname. Clients outside of every class have no requirements.
+    pub classes: Vec<ClientClass>,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            version: format!("rustedirc-{}", env!("CARGO_PKG_VERSION")),
+            created: None,
+            motd: None,
+            max_sendq: 100 * 1024,
+            flood_burst: 10,
+            flood_refill_ms: 1000,
+            flood_exempt_opers: true,
+            ping_frequency_ms: 120 * 1000,
+            registration_timeout_ms: 60 * 1000,
+            nicklen: 30,
+            userlen: 10,
+            reallen: 50,
+            max_targets: 4,
+            casemapping: CaseMapping::default(),
+            classes: Vec::new(),
+        }
+    }
+}
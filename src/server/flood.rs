@@ -0,0 +1,100 @@
+/// Token bucket limiting the rate of commands a client can issue. Every
+/// command takes some tokens out of the bucket, which is refilled with one
+/// token per `refill_ms` up to its capacity.
+pub struct TokenBucket {
+    capacity: u32,
+    refill_ms: u64,
+    tokens: u32,
+    last_refill: u64,
+}
+
+impl TokenBucket {
+    /// Creates a full bucket.
+    pub fn new(capacity: u32, refill_ms: u64, now: u64) -> TokenBucket {
+        TokenBucket {
+            capacity: capacity,
+            refill_ms: refill_ms,
+            tokens: capacity,
+            last_refill: now,
+        }
+    }
+
+    pub fn tokens(&self) -> u32 { self.tokens }
+
+    /// Takes `cost` tokens out of the bucket. Returns false, leaving the
+    /// bucket as is, if there are not enough of them.
+    pub fn consume(&mut self, cost: u32, now: u64) -> bool {
+        self.refill(now);
+        if cost > self.tokens {
+            false
+        } else {
+            self.tokens -= cost;
+            true
+        }
+    }
+
+    fn refill(&mut self, now: u64) {
+        if now <= self.last_refill {
+            return;
+        }
+        if self.refill_ms == 0 {
+            self.tokens = self.capacity;
+            self.last_refill = now;
+            return;
+        }
+
+        let gained = (now - self.last_refill) / self.refill_ms;
+        if self.tokens as u64 + gained >= self.capacity as u64 {
+            self.tokens = self.capacity;
+            self.last_refill = now;
+        } else {
+            self.tokens += gained as u32;
+            self.last_refill += gained * self.refill_ms;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    pub use super::TokenBucket;
+    pub use hamcrest::{assert_that, is, not, none, equal_to};
+
+    describe! token_bucket {
+        before_each {
+            let mut bucket = TokenBucket::new(5, 1000, 10000);
+        }
+
+        it "starts full" {
+            assert_that(bucket.tokens(), is(equal_to(5)));
+        }
+
+        it "allows a burst up to its capacity" {
+            for _ in 0..5 {
+                assert_that(bucket.consume(1, 10000), is(equal_to(true)));
+            }
+            assert_that(bucket.consume(1, 10000), is(equal_to(false)));
+        }
+
+        it "does not take tokens for a refused command" {
+            bucket.consume(3, 10000);
+            assert_that(bucket.consume(3, 10000), is(equal_to(false)));
+            assert_that(bucket.tokens(), is(equal_to(2)));
+        }
+
+        it "refills one token per period" {
+            bucket.consume(5, 10000);
+
+            assert_that(bucket.consume(1, 10999), is(equal_to(false)));
+            assert_that(bucket.consume(1, 11000), is(equal_to(true)));
+            assert_that(bucket.consume(2, 12500), is(equal_to(false)));
+            assert_that(bucket.consume(2, 13000), is(equal_to(true)));
+        }
+
+        it "never refills over its capacity" {
+            bucket.consume(1, 10000);
+            bucket.consume(0, 100000);
+
+            assert_that(bucket.tokens(), is(equal_to(5)));
+        }
+    }
+}
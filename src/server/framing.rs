@@ -0,0 +1,149 @@
+use message::tags::MAX_CLIENT_TAGS_LENGTH;
+
+/// RFC 1459 limits a message to 512 bytes, including the trailing CR-LF.
+/// IRCv3 message tags come on top of that, with a limit of their own.
+pub const MAX_LINE_LENGTH: usize = 510;
+
+#[derive(Debug, PartialEq)]
+pub enum Frame {
+    /// A complete line, without the terminator.
+    Line(String),
+    /// A line went over the length limit and was dropped.
+    TooLong,
+}
+
+/// Buffers the raw input of a single connection and splits it into lines.
+///
+/// Any of CR, LF or CR-LF terminates a line, empty lines are skipped. A
+/// line with a tags section over `MAX_CLIENT_TAGS_LENGTH`, or with more than
+/// `MAX_LINE_LENGTH` bytes after it, is reported once as `Frame::TooLong` and
+/// the rest of it is discarded up to the next terminator.
+pub struct LineBuffer {
+    buf: Vec<u8>,
+    /// Where the line continues after its tags section, once seen.
+    tags_end: Option<usize>,
+    discarding: bool,
+}
+
+impl LineBuffer {
+    pub fn new() -> LineBuffer {
+        LineBuffer {
+            buf: Vec::new(),
+            tags_end: None,
+            discarding: false,
+        }
+    }
+
+    pub fn push(&mut self, data: &[u8]) -> Vec<Frame> {
+        let mut frames = Vec::new();
+
+        for &byte in data.iter() {
+            if byte == b'\r' || byte == b'\n' {
+                if self.discarding {
+                    self.discarding = false;
+                } else if self.buf.len() > 0 {
+                    frames.push(Frame::Line(String::from_utf8_lossy(&self.buf).into_owned()));
+                }
+                self.buf.clear();
+                self.tags_end = None;
+            } else if !self.discarding {
+                // The tags section runs up to and including the first space.
+                let limit = match self.tags_end {
+                    Some(tags_end) => tags_end + MAX_LINE_LENGTH,
+                    None if self.buf.first() == Some(&b'@') => MAX_CLIENT_TAGS_LENGTH,
+                    None => MAX_LINE_LENGTH,
+                };
+                if self.buf.len() == limit {
+                    self.buf.clear();
+                    self.tags_end = None;
+                    self.discarding = true;
+                    frames.push(Frame::TooLong);
+                } else {
+                    self.buf.push(byte);
+                    if byte == b' ' && self.tags_end.is_none() && self.buf[0] == b'@' {
+                        self.tags_end = Some(self.buf.len());
+                    }
+                }
+            }
+        }
+
+        frames
+    }
+}
+
+#[cfg(test)]
+mod test {
+    pub use std::iter::repeat;
+    pub use super::{LineBuffer, Frame, MAX_LINE_LENGTH};
+    pub use message::tags::MAX_CLIENT_TAGS_LENGTH;
+    pub use hamcrest::{assert_that, is, not, none, equal_to};
+
+    describe! line_buffer {
+        before_each {
+            let mut lb = LineBuffer::new();
+        }
+
+        it "splits lines on CR-LF" {
+            assert_that(lb.push(b"NICK test\r\nUSER a b c d\r\n"), is(equal_to(vec![
+                Frame::Line("NICK test".to_string()),
+                Frame::Line("USER a b c d".to_string())])));
+        }
+
+        it "splits lines on bare LF and bare CR" {
+            assert_that(lb.push(b"NICK test\nUSER a b c d\rPING x\n"), is(equal_to(vec![
+                Frame::Line("NICK test".to_string()),
+                Frame::Line("USER a b c d".to_string()),
+                Frame::Line("PING x".to_string())])));
+        }
+
+        it "skips empty lines" {
+            assert_that(lb.push(b"\r\n\r\n\nPING x\r\n"), is(equal_to(vec![
+                Frame::Line("PING x".to_string())])));
+        }
+
+        it "buffers partial lines between chunks" {
+            assert_that(lb.push(b"NICK te"), is(equal_to(vec![])));
+            assert_that(lb.push(b"st\r"), is(equal_to(vec![Frame::Line("NICK test".to_string())])));
+            assert_that(lb.push(b"\n"), is(equal_to(vec![])));
+        }
+
+        it "accepts a line of exactly the maximum length" {
+            let line: String = repeat('a').take(MAX_LINE_LENGTH).collect();
+            let input = format!("{}\r\n", line);
+
+            assert_that(lb.push(input.as_bytes()), is(equal_to(vec![Frame::Line(line)])));
+        }
+
+        it "allows for message tags on top of the limit" {
+            let tags: String = repeat('a').take(MAX_CLIENT_TAGS_LENGTH - 2).collect();
+            let rest: String = repeat('a').take(MAX_LINE_LENGTH).collect();
+            let line = format!("@{} {}", tags, rest);
+            let input = format!("{}\r\n", line);
+
+            assert_that(lb.push(input.as_bytes()), is(equal_to(vec![Frame::Line(line)])));
+        }
+
+        it "rejects an over-long tags section" {
+            let tags: String = repeat('a').take(MAX_CLIENT_TAGS_LENGTH).collect();
+            let input = format!("@{} PING x\r\n", tags);
+
+            assert_that(lb.push(input.as_bytes()), is(equal_to(vec![Frame::TooLong])));
+        }
+
+        it "applies the regular limit to the part after the tags" {
+            let rest: String = repeat('a').take(MAX_LINE_LENGTH + 1).collect();
+            let input = format!("@a=b {}\r\nPING x\r\n", rest);
+
+            assert_that(lb.push(input.as_bytes()), is(equal_to(vec![
+                Frame::TooLong, Frame::Line("PING x".to_string())])));
+        }
+
+        it "rejects an over-long line and resumes after its terminator" {
+            let line: String = repeat('a').take(MAX_LINE_LENGTH + 100).collect();
+
+            assert_that(lb.push(line.as_bytes()), is(equal_to(vec![Frame::TooLong])));
+            assert_that(lb.push(b"aaaa\r\nPING x\r\n"), is(equal_to(vec![
+                Frame::Line("PING x".to_string())])));
+        }
+    }
+}
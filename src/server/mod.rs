@@ -1,33 +1,88 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::Write;
 use std::cell::{Cell, RefCell};
 use core;
+use time;
 
 use uidgen::TS6UIDGenerator;
-use message::Message;
+use message::{Message, ParseError, Prefix, Reply};
+use message::error::IRCError;
+use self::framing::{LineBuffer, Frame};
+use self::channel::Channel;
+use self::sendq::SendQ;
+use self::flood::TokenBucket;
+use self::clock::{Clock, SystemClock};
+pub use self::config::Config;
+pub use self::casemapping::CaseMapping;
+pub use self::password::{Secret, ClientClass};
+pub use self::command::{CommandSpec, CommandHandler};
 
 mod command;
+pub mod framing;
+pub mod channel;
+pub mod config;
+pub mod sendq;
+pub mod flood;
+pub mod clock;
+pub mod casemapping;
+pub mod password;
 
 pub type Token = usize;
 
+/// Modes advertised with RPL_MYINFO.
+const USER_MODES: &'static str = "o";
+const CHANNEL_MODES: &'static str = "n";
+
+/// RPL_ISUPPORT lines carry at most this many tokens.
+const MAX_ISUPPORT_TOKENS: usize = 13;
+
 pub struct Client {
     token: Token,
     nickname: RefCell<Option<String>>,
     username: RefCell<Option<String>>,
     realname: RefCell<Option<String>>,
     hostname: RefCell<Option<String>>,
+    secure: Cell<bool>,
+    certfp: RefCell<Option<String>>,
+    /// Username confirmed by an ident lookup.
+    ident: RefCell<Option<String>>,
+    /// Sent with PASS, only kept until registration.
+    password: RefCell<Option<String>>,
     out_socket: RefCell<Box<Write>>,
+    linebuf: RefCell<LineBuffer>,
+    sendq: RefCell<SendQ>,
+    flood: RefCell<TokenBucket>,
+    oper: Cell<bool>,
+    registered: Cell<bool>,
+    connected_at: u64,
+    last_activity: Cell<u64>,
+    ping_sent: Cell<bool>,
+    exit_reason: RefCell<Option<String>>,
 }
 
 impl Client {
-    pub fn new(token: Token, out_socket: Box<Write>, hostname: String) -> Client {
+    pub fn new(token: Token, out_socket: Box<Write>, hostname: String, config: &Config,
+            now: u64) -> Client {
         Client {
             token: token,
             nickname: RefCell::new(None),
             username: RefCell::new(None),
             realname: RefCell::new(None),
             hostname: RefCell::new(Some(hostname)),
+            secure: Cell::new(false),
+            certfp: RefCell::new(None),
+            ident: RefCell::new(None),
+            password: RefCell::new(None),
             out_socket: RefCell::new(out_socket),
+            linebuf: RefCell::new(LineBuffer::new()),
+            sendq: RefCell::new(SendQ::new(config.max_sendq)),
+            flood: RefCell::new(TokenBucket::new(config.flood_burst, config.flood_refill_ms, now)),
+            oper: Cell::new(false),
+            registered: Cell::new(false),
+            connected_at: now,
+            last_activity: Cell::new(now),
+            ping_sent: Cell::new(false),
+            exit_reason: RefCell::new(None),
         }
     }
 
@@ -40,15 +95,86 @@ impl Client {
     pub fn realname(&self) -> Option<String> { self.realname.borrow().clone() }
     pub fn set_realname(&self, new: String) { *self.realname.borrow_mut() = Some(new); }
 
-    pub fn registered(&self) -> bool {
-        self.username.borrow().is_some() && self.nickname.borrow().is_some()
+    /// Whether the client is connected over TLS.
+    pub fn secure(&self) -> bool { self.secure.get() }
+
+    /// SHA-256 fingerprint of the client's TLS certificate, as lowercase hex.
+    pub fn certfp(&self) -> Option<String> { self.certfp.borrow().clone() }
+
+    pub fn is_oper(&self) -> bool { self.oper.get() }
+    pub fn set_oper(&self, oper: bool) { self.oper.set(oper); }
+
+    /// Whether the client completed the registration and was welcomed.
+    pub fn registered(&self) -> bool { self.registered.get() }
+
+    /// Whether the client gave all the details needed for registration
+    /// but was not welcomed yet.
+    fn ready_to_register(&self) -> bool {
+        !self.registered() && self.username.borrow().is_some() && self.nickname.borrow().is_some()
+    }
+
+    /// Queues a line for sending, the CR-LF is appended. The client is
+    /// dropped if its SendQ overflows.
+    pub fn send(&self, line: &str) {
+        self.enqueue(format!("{}\r\n", line).as_bytes());
+    }
+
+    /// Queues the wire form of the message. Messages which have none are
+    /// dropped.
+    pub fn send_message(&self, message: &Message) {
+        let mut data: Vec<u8> = Vec::new();
+        if message.write_to(&mut data).is_ok() {
+            self.enqueue(&data);
+        }
+    }
+
+    fn enqueue(&self, data: &[u8]) {
+        let mut sendq = self.sendq.borrow_mut();
+        if !sendq.push(data) {
+            sendq.clear();
+            self.exit("Max SendQ exceeded");
+        }
     }
 
+    /// Writes out as much of the SendQ as the socket accepts. Returns true if
+    /// there is still data left to send.
+    fn flush(&self) -> bool {
+        let mut sendq = self.sendq.borrow_mut();
+        if let Err(e) = sendq.flush_to(&mut **self.out_socket.borrow_mut()) {
+            sendq.clear();
+            self.exit(format!("Write error: {}", e).as_str());
+        }
+        !sendq.is_empty()
+    }
+
+    /// Schedules the client to be disconnected once the server is done with
+    /// the current line. Only the first reason given is kept.
+    pub fn exit(&self, reason: &str) {
+        let mut exit_reason = self.exit_reason.borrow_mut();
+        if exit_reason.is_none() {
+            *exit_reason = Some(reason.to_string());
+        }
+    }
+
+    pub fn exiting(&self) -> bool { self.exit_reason.borrow().is_some() }
+
+    /// Nickname to address numerics to, `*` if none was set yet.
+    pub fn reply_target(&self) -> String {
+        self.nickname().unwrap_or("*".to_string())
+    }
+
+    /// The nick!user@host form of the client, with whatever parts are known.
     pub fn prefix(&self) -> String {
-        format!("{}!{}@{}",
-            self.nickname.borrow().as_ref().unwrap(),
-            self.username.borrow().as_ref().unwrap(),
-            self.hostname.borrow().as_ref().unwrap())
+        let nickname = self.nickname.borrow();
+        let username = self.username.borrow();
+        let hostname = self.hostname.borrow();
+
+        let prefix = Prefix::User {
+            nick: nickname.as_ref().map_or("*", |n| n.as_str()),
+            user: username.as_ref().map(|u| u.as_str()),
+            host: hostname.as_ref().map(|h| h.as_str()),
+        };
+        prefix.to_string()
     }
 }
 
@@ -62,37 +188,239 @@ pub struct Server {
     name: String,
     display_name: String,
     sid: String,
+    config: Config,
     clients_tok: HashMap<Token, Client>,
+    /// Keyed by the nickname folded with the configured casemapping.
     nicknames: RefCell<HashMap<String, Token>>,
+    /// Keyed by the channel name folded with the configured casemapping.
+    channels: RefCell<HashMap<String, Channel>>,
+    closed: RefCell<Vec<Token>>,
+    /// Date sent with RPL_CREATED.
+    created: String,
+    /// Highest amount of registered clients seen at once.
+    max_users: Cell<usize>,
+    /// Keyed by the upper case command name.
+    commands: HashMap<String, CommandSpec>,
+    clock: Box<Clock>,
     uuidgen: RefCell<TS6UIDGenerator>,
 }
 
 impl Server {
     pub fn new(name: String, display_name: String, sid: String) -> Server {
+        Server::with_config(name, display_name, sid, Config::default())
+    }
+
+    pub fn with_config(name: String, display_name: String, sid: String, config: Config) -> Server {
         let uuidgen = TS6UIDGenerator::new(sid.as_str());
-        Server {
+        let created = match config.created {
+            Some(ref created) => created.clone(),
+            None => time::strftime("%a %b %d %Y at %H:%M:%S UTC", &time::now_utc()).unwrap_or(String::new()),
+        };
+        let mut server = Server {
             name: name,
             display_name: display_name,
             sid: sid,
+            config: config,
             clients_tok: HashMap::new(),
             nicknames: RefCell::new(HashMap::new()),
+            channels: RefCell::new(HashMap::new()),
+            closed: RefCell::new(Vec::new()),
+            created: created,
+            max_users: Cell::new(0),
+            commands: HashMap::new(),
+            clock: Box::new(SystemClock),
             uuidgen: RefCell::new(uuidgen),
+        };
+
+        for (name, spec) in command::builtin_commands().into_iter() {
+            server.register_command(name, spec);
         }
+        server
     }
 
     pub fn name(&self) -> &String { &self.name }
 
+    pub fn casemapping(&self) -> CaseMapping { self.config.casemapping }
+
+    /// Replaces the time source, must be done before accepting any
+    /// connections.
+    pub fn set_clock(&mut self, clock: Box<Clock>) {
+        self.clock = clock;
+    }
+
     pub fn accept_connection(&mut self, out_socket: Box<Write>, token: Token, hostname: String) {
-        let client = Client::new(token, out_socket, hostname);
+        let client = Client::new(token, out_socket, hostname, &self.config, self.clock.now());
 
         self.clients_tok.insert(token, client);
     }
 
+    /// Marks the connection as using TLS, with the fingerprint of the client
+    /// certificate if one was presented.
+    pub fn set_secure(&self, token: Token, certfp: Option<String>) {
+        if let Some(client) = self.client_by_token(token) {
+            client.secure.set(true);
+            *client.certfp.borrow_mut() = certfp;
+        }
+    }
+
+    /// Records the username an ident lookup returned for the connection,
+    /// which is then used instead of the one given with USER if it is a valid
+    /// username.
+    pub fn set_ident(&self, token: Token, ident: String) {
+        if let Some(client) = self.client_by_token(token) {
+            *client.ident.borrow_mut() = Some(ident);
+        }
+    }
+
+    /// Feeds a raw chunk of data received from the connection, processing
+    /// every line completed by it.
+    pub fn process_data(&mut self, token: Token, data: &[u8]) {
+        let frames = if let Some(client) = self.client_by_token(token) {
+            client.linebuf.borrow_mut().push(data)
+        } else {
+            return
+        };
+
+        for frame in frames.into_iter() {
+            match frame {
+                Frame::Line(line) => self.process_line(token, line.as_str()),
+                Frame::TooLong => if let Some(client) = self.client_by_token(token) {
+                    self.send_reply(client, Reply::InputTooLong);
+                },
+            }
+        }
+        self.flush();
+    }
+
     pub fn process_line(&mut self, token: Token, line: &str) {
-        if let Some(message) = Message::new(line) {
-            self.resolve_command(message, self.client_by_token(token).unwrap());
+        if let Some(client) = self.client_by_token(token) {
+            let parsed = Message::new(line);
+
+            client.last_activity.set(self.clock.now());
+            client.ping_sent.set(false);
+            // Lines which do not parse are charged like a regular command.
+            let command = match parsed {
+                Ok(ref message) => message.command,
+                Err(_) => "",
+            };
+            if self.check_flood(client, command) {
+                match parsed {
+                    Ok(message) => self.resolve_command(message, client),
+                    Err(ParseError::InvalidCommand(command)) =>
+                        self.send_error(client, IRCError::unknown_command(command.as_str())),
+                    Err(ParseError::TagsTooLong) =>
+                        self.send_reply(client, Reply::InputTooLong),
+                    Err(ParseError::IllegalCharacter) => client.exit("Protocol violation"),
+                    // Nothing sensible can be done about the rest, the line is
+                    // silently dropped.
+                    Err(_) => (),
+                }
+            }
+        }
+        self.flush();
+    }
+
+    /// Drops the clients scheduled to exit and tries to write out the SendQs
+    /// of the rest. Returns the tokens of the clients whose sockets could not
+    /// take all the queued data.
+    pub fn flush(&mut self) -> Vec<Token> {
+        self.process_exits();
+        self.clients_tok.values()
+            .filter(|c| c.flush())
+            .map(|c| c.token)
+            .collect()
+    }
+
+    /// Pings idle clients and drops the ones which did not answer in time or
+    /// failed to register. Meant to be called periodically.
+    pub fn check_timeouts(&mut self) {
+        let now = self.clock.now();
+
+        for client in self.clients_tok.values() {
+            // The clock may have been stepped back since.
+            let idle = now.saturating_sub(client.last_activity.get());
+
+            if !client.registered() {
+                if now.saturating_sub(client.connected_at) >= self.config.registration_timeout_ms {
+                    client.exit("Registration timed out");
+                }
+            } else if client.ping_sent.get() {
+                if idle >= 2 * self.config.ping_frequency_ms {
+                    client.exit(format!("Ping timeout: {} seconds", idle / 1000).as_str());
+                }
+            } else if idle >= self.config.ping_frequency_ms {
+                client.send(format!("PING :{}", self.name).as_str());
+                client.ping_sent.set(true);
+            }
+        }
+
+        self.flush();
+    }
+
+    /// Charges the client for the command, dropping it if it went over the
+    /// allowed rate.
+    fn check_flood(&self, client: &Client, command: &str) -> bool {
+        if client.is_oper() && self.config.flood_exempt_opers {
+            return true;
+        }
+        // Unknown commands cost as much as a regular one.
+        let cost = self.command_spec(command).map_or(1, |spec| spec.flood_cost);
+        if client.flood.borrow_mut().consume(cost, self.clock.now()) {
+            true
         } else {
-            // TODO: received garbage
+            client.exit("Excess Flood");
+            false
+        }
+    }
+
+    /// Removes the client from every index, sends it the closing ERROR and
+    /// notifies the clients sharing a channel with it.
+    pub fn disconnect(&mut self, token: Token, reason: &str) {
+        let client = match self.clients_tok.remove(&token) {
+            Some(client) => client,
+            None => return,
+        };
+
+        if let Some(nickname) = client.nickname() {
+            let key = self.config.casemapping.fold(nickname.as_str());
+            let mut nicknames = self.nicknames.borrow_mut();
+            if nicknames.get(&key) == Some(&token) {
+                nicknames.remove(&key);
+            }
+        }
+
+        if client.registered() {
+            let prefix = client.prefix();
+            for peer in self.peers_of(&client).into_iter() {
+                if let Some(peer) = self.client_by_token(peer) {
+                    peer.send(format!(":{} QUIT :{}", prefix, reason).as_str());
+                }
+            }
+        }
+        self.part_all(&client);
+
+        client.send(format!("ERROR :Closing Link: {} ({})",
+            client.hostname.borrow().as_ref().unwrap(), reason).as_str());
+        client.flush();
+        self.closed.borrow_mut().push(token);
+    }
+
+    /// Returns the tokens of connections dropped since the last call, so
+    /// that the caller can close the underlying sockets.
+    pub fn drain_closed(&self) -> Vec<Token> {
+        let mut closed = self.closed.borrow_mut();
+        let tokens = closed.clone();
+        closed.clear();
+        tokens
+    }
+
+    fn process_exits(&mut self) {
+        let exits: Vec<(Token, String)> = self.clients_tok.values()
+            .filter_map(|c| c.exit_reason.borrow().clone().map(|r| (c.token, r)))
+            .collect();
+
+        for (token, reason) in exits.into_iter() {
+            self.disconnect(token, reason.as_str());
         }
     }
 
@@ -101,16 +429,166 @@ impl Server {
     }
 
     pub fn client_by_nickname(&self, nickname: &String) -> Option<&Client> {
-        let tok = self.nicknames.borrow().get(nickname).unwrap().clone();
-        self.client_by_token(tok)
+        let key = self.config.casemapping.fold(nickname.as_str());
+        let tok = self.nicknames.borrow().get(&key).cloned();
+        tok.and_then(|tok| self.client_by_token(tok))
+    }
+
+    fn join_channel(&self, name: &str, client: &Client) {
+        let key = self.config.casemapping.fold(name);
+        let mut channels = self.channels.borrow_mut();
+        if !channels.contains_key(&key) {
+            channels.insert(key.clone(), Channel::new(name.to_string()));
+        }
+        channels.get_mut(&key).unwrap().add_member(client.token);
+    }
+
+    fn part_all(&self, client: &Client) {
+        let mut channels = self.channels.borrow_mut();
+        let mut emptied = Vec::new();
+        for (name, channel) in channels.iter_mut() {
+            channel.remove_member(client.token);
+            if channel.is_empty() {
+                emptied.push(name.clone());
+            }
+        }
+        for name in emptied.iter() {
+            channels.remove(name);
+        }
+    }
+
+    /// Tokens of all the other clients sharing at least one channel with
+    /// the given one.
+    fn peers_of(&self, client: &Client) -> Vec<Token> {
+        let mut peers = HashSet::new();
+        for channel in self.channels.borrow().values() {
+            if channel.has_member(client.token) {
+                peers.extend(channel.members().into_iter());
+            }
+        }
+        peers.remove(&client.token);
+        peers.into_iter().collect()
+    }
+
+    /// Changes the nickname of the client, keeping the index in sync.
+    fn change_nickname(&self, client: &Client, nickname: &str) {
+        let mut nicknames = self.nicknames.borrow_mut();
+        if let Some(old) = client.nickname() {
+            let key = self.config.casemapping.fold(old.as_str());
+            if nicknames.get(&key) == Some(&client.token) {
+                nicknames.remove(&key);
+            }
+        }
+        nicknames.insert(self.config.casemapping.fold(nickname), client.token);
+        client.set_nickname(nickname.to_string());
+    }
+
+    /// Completes the registration once the client sent both NICK and USER,
+    /// provided it gave the password its class requires.
+    fn try_register(&self, client: &Client) {
+        if !client.ready_to_register() {
+            return;
+        }
+
+        let password = client.password.borrow_mut().take();
+        let required = self.client_class(client).and_then(|class| class.password.as_ref());
+        if let Some(secret) = required {
+            if !password.map_or(false, |password| secret.matches(password.as_str())) {
+                self.send_error(client, IRCError::from(Reply::PasswdMismatch));
+                client.exit("Bad Password");
+                return;
+            }
+        }
+        self.register_client(client);
+    }
+
+    fn client_class(&self, client: &Client) -> Option<&ClientClass> {
+        let hostname = match client.hostname.borrow().clone() {
+            Some(hostname) => hostname,
+            None => return None,
+        };
+        self.config.classes.iter().find(|class| class.matches(hostname.as_str()))
     }
 
     fn register_client(&self, client: &Client) {
+        client.registered.set(true);
+        let users = self.clients_tok.values().filter(|c| c.registered()).count();
+        if users > self.max_users.get() {
+            self.max_users.set(users);
+        }
+
         let nickname = client.nickname().unwrap();
-        self.nicknames.borrow_mut().insert(nickname.clone(), client.token);
-        write!(client.out_socket.borrow_mut(),
-            ":{} 001 {} :Welcome to the {} Internet Relay Chat Network {}\r\n",
-            self.name, nickname, self.display_name, nickname);
+        self.send_reply(client, Reply::Welcome {
+            network: self.display_name.as_str(),
+            nick: nickname.as_str(),
+        });
+        self.send_reply(client, Reply::YourHost {
+            server: self.name.as_str(),
+            version: self.config.version.as_str(),
+        });
+        self.send_reply(client, Reply::Created { date: self.created.as_str() });
+        self.send_reply(client, Reply::MyInfo {
+            server: self.name.as_str(),
+            version: self.config.version.as_str(),
+            user_modes: USER_MODES,
+            channel_modes: CHANNEL_MODES,
+        });
+        let tokens = self.isupport_tokens();
+        for chunk in tokens.chunks(MAX_ISUPPORT_TOKENS) {
+            self.send_reply(client, Reply::ISupport { tokens: chunk });
+        }
+        self.send_lusers(client);
+        self.send_motd(client);
+    }
+
+    /// Features advertised with RPL_ISUPPORT.
+    pub fn isupport_tokens(&self) -> Vec<String> {
+        vec![
+            format!("NETWORK={}", self.display_name),
+            self.config.casemapping.isupport_token(),
+            "CHANTYPES=#&".to_string(),
+            format!("NICKLEN={}", self.config.nicklen),
+            format!("USERLEN={}", self.config.userlen),
+            format!("REALLEN={}", self.config.reallen),
+            format!("MAXTARGETS={}", self.config.max_targets),
+        ]
+    }
+
+    /// Sends the user and channel counts, leaving out the lines which
+    /// would report nothing.
+    pub fn send_lusers(&self, client: &Client) {
+        let clients = self.clients_tok.len();
+        let users = self.clients_tok.values().filter(|c| c.registered()).count();
+        let opers = self.clients_tok.values().filter(|c| c.is_oper()).count();
+        let channels = self.channels.borrow().len();
+        let max = self.max_users.get();
+
+        self.send_reply(client, Reply::LuserClient { users: users, invisible: 0, servers: 1 });
+        if opers > 0 {
+            self.send_reply(client, Reply::LuserOp { count: opers });
+        }
+        if clients > users {
+            self.send_reply(client, Reply::LuserUnknown { count: clients - users });
+        }
+        if channels > 0 {
+            self.send_reply(client, Reply::LuserChannels { count: channels });
+        }
+        self.send_reply(client, Reply::LuserMe { clients: clients, servers: 0 });
+        self.send_reply(client, Reply::LocalUsers { current: users, max: max });
+        self.send_reply(client, Reply::GlobalUsers { current: users, max: max });
+    }
+
+    pub fn send_motd(&self, client: &Client) {
+        match self.config.motd {
+            Some(ref motd) => {
+                self.send_reply(client, Reply::MotdStart { server: self.name.as_str() });
+                for line in motd.iter() {
+                    self.send_reply(client, Reply::Motd { line: line.as_str() });
+                }
+                self.send_reply(client, Reply::EndOfMotd);
+            },
+            None => self.send_reply(client, Reply::NoMotd),
+        }
     }
 }
 
@@ -118,20 +596,28 @@ impl Server {
 mod test {
     use std::rc::Rc;
     use std::io;
-    use std::cell::RefCell;
+    use std::cell::{Cell, RefCell};
 
+    pub use std::iter::repeat;
     pub use server::*;
     pub use hamcrest::{assert_that, is, not, none, equal_to};
 
-    pub struct TestSock { buf: Rc<RefCell<Vec<u8>>> }
+    pub struct TestSock { buf: Rc<RefCell<Vec<u8>>>, blocked: Rc<Cell<bool>> }
 
     impl TestSock {
         pub fn new() -> TestSock {
             TestSock {
-                buf: Rc::new(RefCell::new(Vec::new()))
+                buf: Rc::new(RefCell::new(Vec::new())),
+                blocked: Rc::new(Cell::new(false)),
             }
         }
 
+        /// Makes the socket refuse writes as a non-blocking socket with a
+        /// full buffer would.
+        pub fn set_blocked(&self, blocked: bool) {
+            self.blocked.set(blocked);
+        }
+
         pub fn data(&self) -> String {
             String::from_utf8(self.buf.borrow().clone()).ok().unwrap()
         }
@@ -143,6 +629,9 @@ mod test {
 
     impl io::Write for TestSock {
         fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.blocked.get() {
+                return Err(io::Error::new(io::ErrorKind::WouldBlock, "blocked"));
+            }
             self.buf.borrow_mut().write(buf)
         }
         fn flush(&mut self) -> io::Result<()> {
@@ -153,7 +642,8 @@ mod test {
     impl Clone for TestSock {
         fn clone(&self) -> Self {
             TestSock {
-                buf: self.buf.clone()
+                buf: self.buf.clone(),
+                blocked: self.blocked.clone(),
             }
         }
     }
@@ -172,6 +662,102 @@ mod test {
             assert_that(server.client_by_token(8), is(not(none())));
         }
 
+        it "is resolved by nickname regardless of case" {
+            server.process_line(token, "NICK Test[a]");
+            server.process_line(token, "USER guest 0 * :Ronnie Reagan");
+
+            assert_that(server.client_by_nickname(&"TEST{A}".to_string()), is(not(none())));
+        }
+
+        it "is removed from the server on disconnect" {
+            server.process_line(token, "NICK test");
+            server.process_line(token, "USER guest 0 * :Ronnie Reagan");
+            server.disconnect(token, "Remote host closed the connection");
+
+            assert_that(server.client_by_token(8), is(none()));
+            assert_that(server.client_by_nickname(&"test".to_string()), is(none()));
+            assert_that(server.drain_closed(), is(equal_to(vec![8])));
+            assert_that(server.drain_closed(), is(equal_to(vec![])));
+        }
+
+        it "replies ERR_UNKNOWNCOMMAND to an invalid command" {
+            server.process_line(token, "N1CK test");
+
+            assert_that(sock.data().as_str(),
+                is(equal_to(":test.local 421 * N1CK :Unknown command\r\n")));
+        }
+
+        it "rejects over-long tags with ERR_INPUTTOOLONG" {
+            let tags: String = repeat('a').take(5000).collect();
+            server.process_line(token, format!("@a={} PING x", tags).as_str());
+
+            assert_that(sock.data().as_str(),
+                is(equal_to(":test.local 417 * :Input line was too long\r\n")));
+        }
+
+        it "ignores a prefix without command" {
+            server.process_line(token, ":test.local");
+
+            assert_that(sock.data().as_str(), is(equal_to("")));
+            assert_that(server.client_by_token(8), is(not(none())));
+        }
+
+        it "ignores lines with too many parameters" {
+            server.process_line(token, "NICK 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16");
+
+            assert_that(sock.data().as_str(), is(equal_to("")));
+            assert_that(server.client_by_token(8).unwrap().nickname(), is(none()));
+        }
+
+        it "drops a client sending a NUL" {
+            server.process_data(token, b"NICK te\0st\r\n");
+
+            assert_that(server.client_by_token(8), is(none()));
+            assert_that(sock.data().as_str(),
+                is(equal_to("ERROR :Closing Link: 127.0.0.1 (Protocol violation)\r\n")));
+        }
+
+        it "has a partial prefix before registration" {
+            server.process_line(token, "NICK test");
+            let user = server.client_by_token(8).unwrap();
+
+            assert_that(user.prefix(), is(equal_to("test@127.0.0.1".to_string())));
+        }
+
+        it "is not secure by default" {
+            let user = server.client_by_token(8).unwrap();
+
+            assert_that(user.secure(), is(equal_to(false)));
+            assert_that(user.certfp(), is(none()));
+        }
+
+        it "records TLS details" {
+            server.set_secure(token, Some("0a1b2c".to_string()));
+            let user = server.client_by_token(8).unwrap();
+
+            assert_that(user.secure(), is(equal_to(true)));
+            assert_that(user.certfp(), is(equal_to(Some("0a1b2c".to_string()))));
+        }
+
+        it "processes lines received in arbitrary chunks" {
+            server.process_data(token, b"NICK te");
+            server.process_data(token, b"st\r\nUSER guest 0 * :Ronnie");
+            server.process_data(token, b" Reagan\n");
+
+            let user = server.client_by_token(8).unwrap();
+
+            assert_that(user.nickname().unwrap().as_str(), is(equal_to("test")));
+            assert_that(user.realname().unwrap().as_str(), is(equal_to("Ronnie Reagan")));
+        }
+
+        it "rejects over-long lines with ERR_INPUTTOOLONG" {
+            let line: String = repeat('a').take(600).collect();
+            server.process_data(token, format!("PRIVMSG test :{}\r\n", line).as_bytes());
+
+            assert_that(sock.data().as_str(),
+                is(equal_to(":test.local 417 * :Input line was too long\r\n")));
+        }
+
         it "registers user upon NICK/USER pair sent" {
             server.process_line(token, "NICK test");
             server.process_line(token, "USER guest 0 * :Ronnie Reagan");
@@ -186,16 +772,290 @@ mod test {
             server.process_line(token, "NICK test");
 
             let outbuf = sock.data();
-            assert_that(outbuf.as_str(),
-                is(equal_to(":test.local 001 test :Welcome to the TestLocal Internet Relay Chat Network test\r\n")));
+            assert_that(outbuf.starts_with(
+                ":test.local 001 test :Welcome to the TestLocal Internet Relay Chat Network test\r\n"),
+                is(equal_to(true)));
         }
 
         it "sends welcome to registered user when NICK/USER is sent" {
             server.process_line(token, "NICK test");
             server.process_line(token, "USER guest 0 * :Ronnie Reagan");
 
+            assert_that(sock.data().starts_with(
+                ":test.local 001 test :Welcome to the TestLocal Internet Relay Chat Network test\r\n"),
+                is(equal_to(true)));
+        }
+
+        it "sends ERR_NOMOTD without a MOTD" {
+            server.process_line(token, "NICK test");
+            server.process_line(token, "USER guest 0 * :Ronnie Reagan");
+
+            assert_that(sock.data().ends_with(":test.local 422 test :MOTD File is missing\r\n"), is(equal_to(true)));
+        }
+
+        it "sends welcome only once" {
+            server.process_line(token, "NICK test");
+            server.process_line(token, "USER guest 0 * :Ronnie Reagan");
+            sock.clear();
+            server.process_line(token, "NICK other");
+
+            assert_that(sock.data().contains(" 001 "), is(equal_to(false)));
+        }
+    }
+
+    describe! welcome_burst {
+        before_each {
+            let mut config = Config::default();
+            config.version = "rustedirc-test".to_string();
+            config.created = Some("today".to_string());
+            config.motd = Some(vec!["Hello there".to_string()]);
+            let mut server = Server::with_config("test.local".to_string(), "TestLocal".to_string(),
+                "42X".to_string(), config);
+
+            let sock = Box::new(TestSock::new());
+            let token = 8;
+
+            server.accept_connection(sock.clone(), token, "127.0.0.1".to_string());
+        }
+
+        it "is sent upon registration" {
+            server.process_line(token, "NICK test");
+            server.process_line(token, "USER guest 0 * :Ronnie Reagan");
+
+            assert_that(sock.data().as_str(), is(equal_to(
+                ":test.local 001 test :Welcome to the TestLocal Internet Relay Chat Network test\r\n\
+                 :test.local 002 test :Your host is test.local, running version rustedirc-test\r\n\
+                 :test.local 003 test :This server was created today\r\n\
+                 :test.local 004 test test.local rustedirc-test o n\r\n\
+                 :test.local 005 test NETWORK=TestLocal CASEMAPPING=rfc1459 CHANTYPES=#& NICKLEN=30 \
+                     USERLEN=10 REALLEN=50 MAXTARGETS=4 :are supported by this server\r\n\
+                 :test.local 251 test :There are 1 users and 0 invisible on 1 servers\r\n\
+                 :test.local 255 test :I have 1 clients and 0 servers\r\n\
+                 :test.local 265 test 1 1 :Current local users 1, max 1\r\n\
+                 :test.local 266 test 1 1 :Current global users 1, max 1\r\n\
+                 :test.local 375 test :- test.local Message of the day - \r\n\
+                 :test.local 372 test :- Hello there\r\n\
+                 :test.local 376 test :End of /MOTD command\r\n")));
+        }
+
+        it "counts other connections and channels" {
+            let peer_sock = Box::new(TestSock::new());
+            server.accept_connection(peer_sock.clone(), 9, "127.0.0.2".to_string());
+            server.process_line(9, "NICK peer");
+            server.process_line(9, "USER peer 0 * :Peer");
+            server.client_by_token(9).unwrap().set_oper(true);
+            server.join_channel("#test", server.client_by_token(9).unwrap());
+            server.accept_connection(Box::new(TestSock::new()), 10, "127.0.0.3".to_string());
+
+            server.process_line(token, "NICK test");
+            server.process_line(token, "USER guest 0 * :Ronnie Reagan");
+
+            let data = sock.data();
+            assert_that(data.contains(":test.local 251 test :There are 2 users and 0 invisible on 1 servers\r\n\
+                :test.local 252 test 1 :operator(s) online\r\n\
+                :test.local 253 test 1 :unknown connection(s)\r\n\
+                :test.local 254 test 1 :channels formed\r\n\
+                :test.local 255 test :I have 3 clients and 0 servers\r\n\
+                :test.local 265 test 2 2 :Current local users 2, max 2\r\n"), is(equal_to(true)));
+        }
+    }
+
+    describe! sendq {
+        before_each {
+            let mut config = Config::default();
+            config.max_sendq = 2048;
+            config.flood_burst = 100;
+            let mut server = Server::with_config("test.local".to_string(), "TestLocal".to_string(),
+                "42X".to_string(), config);
+
+            let sock = Box::new(TestSock::new());
+            let token = 8;
+            let peer_sock = Box::new(TestSock::new());
+            let peer_token = 9;
+
+            server.accept_connection(sock.clone(), token, "127.0.0.1".to_string());
+            server.accept_connection(peer_sock.clone(), peer_token, "127.0.0.2".to_string());
+            server.process_line(token, "NICK test");
+            server.process_line(token, "USER guest 0 * :Ronnie Reagan");
+            server.process_line(peer_token, "NICK peer");
+            server.process_line(peer_token, "USER peer 0 * :Peer");
+            sock.clear();
+        }
+
+        it "queues data while the socket is blocked" {
+            sock.set_blocked(true);
+            server.process_line(peer_token, "PRIVMSG test hello");
+
+            assert_that(server.flush(), is(equal_to(vec![8])));
+            assert_that(sock.data().as_str(), is(equal_to("")));
+
+            sock.set_blocked(false);
+            assert_that(server.flush(), is(equal_to(vec![])));
+            assert_that(sock.data().as_str(), is(equal_to(":peer!~peer@127.0.0.2 PRIVMSG test hello\r\n")));
+        }
+
+        it "drops a client going over the maximum SendQ" {
+            sock.set_blocked(true);
+            for _ in 0..50 {
+                server.process_line(peer_token, "PRIVMSG test :some lengthy message");
+            }
+
+            assert_that(server.client_by_token(token), is(none()));
+            assert_that(server.client_by_token(peer_token), is(not(none())));
+        }
+    }
+
+    describe! flood {
+        before_each {
+            let mut config = Config::default();
+            config.flood_burst = 5;
+            config.flood_refill_ms = 2000;
+            let mut server = Server::with_config("test.local".to_string(), "TestLocal".to_string(),
+                "42X".to_string(), config);
+
+            let clock = clock::ManualClock::new(0);
+            server.set_clock(Box::new(clock.clone()));
+
+            let sock = Box::new(TestSock::new());
+            let token = 8;
+
+            server.accept_connection(sock.clone(), token, "127.0.0.1".to_string());
+            server.process_line(token, "USER guest 0 * :Ronnie Reagan");
+            sock.clear();
+        }
+
+        it "drops a client flooding with commands" {
+            for _ in 0..5 {
+                server.process_line(token, "LIST");
+            }
+
+            assert_that(server.client_by_token(token), is(none()));
+            assert_that(sock.data().ends_with("ERROR :Closing Link: 127.0.0.1 (Excess Flood)\r\n"),
+                is(equal_to(true)));
+        }
+
+        it "allows commands at the configured rate" {
+            for _ in 0..20 {
+                server.process_line(token, "LIST");
+                clock.advance(2000);
+            }
+
+            assert_that(server.client_by_token(token), is(not(none())));
+        }
+
+        it "charges for PING" {
+            for _ in 0..5 {
+                server.process_line(token, "PING x");
+            }
+
+            assert_that(server.client_by_token(token), is(none()));
+        }
+
+        it "charges for lines which do not parse" {
+            for _ in 0..5 {
+                server.process_line(token, "N1CK test");
+            }
+
+            assert_that(server.client_by_token(token), is(none()));
+        }
+
+        it "exempts operators" {
+            server.client_by_token(token).unwrap().set_oper(true);
+            for _ in 0..20 {
+                server.process_line(token, "LIST");
+            }
+
+            assert_that(server.client_by_token(token), is(not(none())));
+        }
+    }
+
+    describe! timeouts {
+        before_each {
+            let mut config = Config::default();
+            config.ping_frequency_ms = 60000;
+            config.registration_timeout_ms = 30000;
+            let mut server = Server::with_config("test.local".to_string(), "TestLocal".to_string(),
+                "42X".to_string(), config);
+
+            let clock = clock::ManualClock::new(0);
+            server.set_clock(Box::new(clock.clone()));
+
+            let sock = Box::new(TestSock::new());
+            let token = 8;
+
+            server.accept_connection(sock.clone(), token, "127.0.0.1".to_string());
+        }
+
+        it "drops clients which do not register in time" {
+            server.process_line(token, "NICK test");
+            clock.advance(30000);
+            server.check_timeouts();
+
+            assert_that(server.client_by_token(token), is(none()));
             assert_that(sock.data().as_str(),
-                is(equal_to(":test.local 001 test :Welcome to the TestLocal Internet Relay Chat Network test\r\n")));
+                is(equal_to("ERROR :Closing Link: 127.0.0.1 (Registration timed out)\r\n")));
+        }
+
+        it "survives the clock going backwards" {
+            clock.advance(10000);
+            server.process_line(token, "NICK test");
+            clock.set(5000);
+            server.check_timeouts();
+
+            assert_that(server.client_by_token(token), is(not(none())));
+        }
+
+        describe! registered_client {
+            before_each {
+                server.process_line(token, "NICK test");
+                server.process_line(token, "USER guest 0 * :Ronnie Reagan");
+                sock.clear();
+            }
+
+            it "is left alone while active" {
+                clock.advance(59999);
+                server.check_timeouts();
+
+                assert_that(sock.data().as_str(), is(equal_to("")));
+            }
+
+            it "is pinged when idle" {
+                clock.advance(60000);
+                server.check_timeouts();
+
+                assert_that(sock.data().as_str(), is(equal_to("PING :test.local\r\n")));
+            }
+
+            it "is pinged only once" {
+                clock.advance(60000);
+                server.check_timeouts();
+                clock.advance(1000);
+                server.check_timeouts();
+
+                assert_that(sock.data().as_str(), is(equal_to("PING :test.local\r\n")));
+            }
+
+            it "is dropped when the ping is not answered" {
+                clock.advance(60000);
+                server.check_timeouts();
+                sock.clear();
+                clock.advance(60000);
+                server.check_timeouts();
+
+                assert_that(server.client_by_token(token), is(none()));
+                assert_that(sock.data().as_str(),
+                    is(equal_to("ERROR :Closing Link: 127.0.0.1 (Ping timeout: 120 seconds)\r\n")));
+            }
+
+            it "is kept when the ping is answered" {
+                clock.advance(60000);
+                server.check_timeouts();
+                server.process_line(token, "PONG :test.local");
+                clock.advance(60000);
+                server.check_timeouts();
+
+                assert_that(server.client_by_token(token), is(not(none())));
+            }
         }
     }
 }
@@ -0,0 +1,158 @@
+use openssl::crypto::hash::{hash, Type as HashType};
+
+/// A configured password, either in clear or as `sha256:SALT:HEX`, HEX being
+/// the SHA-256 digest of the salt followed by the password.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Secret {
+    Plain(String),
+    Sha256 { salt: String, digest: String },
+}
+
+impl Secret {
+    /// Fails for a `sha256:` secret without salt or digest.
+    pub fn parse(secret: &str) -> Option<Secret> {
+        if !secret.starts_with("sha256:") {
+            return Some(Secret::Plain(secret.to_string()));
+        }
+
+        let mut parts = secret[7..].splitn(2, ':');
+        match (parts.next(), parts.next()) {
+            (Some(salt), Some(digest)) if salt.len() > 0 && digest.len() > 0 =>
+                Some(Secret::Sha256 { salt: salt.to_string(), digest: digest.to_lowercase() }),
+            _ => None,
+        }
+    }
+
+    pub fn matches(&self, password: &str) -> bool {
+        match *self {
+            Secret::Plain(ref expected) => constant_time_eq(expected.as_bytes(), password.as_bytes()),
+            Secret::Sha256 { ref salt, ref digest } => {
+                let actual = sha256_hex(format!("{}{}", salt, password).as_str());
+                constant_time_eq(digest.as_bytes(), actual.as_bytes())
+            },
+        }
+    }
+}
+
+pub fn sha256_hex(password: &str) -> String {
+    hash(HashType::SHA256, password.as_bytes()).iter()
+        .map(|b| format!("{:02x}", b)).collect::<Vec<String>>().concat()
+}
+
+/// Compares without bailing out at the first difference, so the time taken
+/// tells nothing about how much of the password was right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b.iter()).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Group of clients sharing the same connection requirements, selected by
+/// matching the client's hostname against the class' masks.
+#[derive(Debug, Clone)]
+pub struct ClientClass {
+    pub name: String,
+    /// Hostname masks, `*` matches any sequence of characters and `?` any
+    /// single one.
+    pub hosts: Vec<String>,
+    /// Password the clients have to send with PASS before registering.
+    pub password: Option<Secret>,
+}
+
+impl ClientClass {
+    pub fn matches(&self, hostname: &str) -> bool {
+        self.hosts.iter().any(|mask| mask_matches(mask.as_str(), hostname))
+    }
+}
+
+/// Matches a glob style mask against the text, ignoring ASCII case.
+pub fn mask_matches(mask: &str, text: &str) -> bool {
+    let mask: Vec<char> = mask.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    // Backtracks to the last star seen when a literal does not match.
+    let (mut m, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if m < mask.len() && (mask[m] == '?' || mask[m] == text[t]) {
+            m += 1;
+            t += 1;
+        } else if m < mask.len() && mask[m] == '*' {
+            star = Some((m, t));
+            m += 1;
+        } else if let Some((star_m, star_t)) = star {
+            m = star_m + 1;
+            t = star_t + 1;
+            star = Some((star_m, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    mask[m..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod test {
+    pub use super::{Secret, ClientClass, mask_matches, sha256_hex};
+    pub use hamcrest::{assert_that, is, not, none, equal_to};
+
+    describe! secret {
+        it "matches plain passwords" {
+            let secret = Secret::parse("hunter2").unwrap();
+
+            assert_that(secret.matches("hunter2"), is(equal_to(true)));
+            assert_that(secret.matches("hunter3"), is(equal_to(false)));
+            assert_that(secret.matches("hunter"), is(equal_to(false)));
+        }
+
+        it "matches salted hashed passwords" {
+            let secret = Secret::parse(
+                "sha256:NaCl:5CCA3FFFB01B0FAEB9F287E0AC91027494C458E565C5716DF9923AEA47947189").unwrap();
+
+            assert_that(secret.matches("hunter2"), is(equal_to(true)));
+            assert_that(secret.matches("NaClhunter2"), is(equal_to(false)));
+            assert_that(secret.matches("sha256:hunter2"), is(equal_to(false)));
+        }
+
+        it "refuses hashed passwords without salt" {
+            assert_that(Secret::parse(
+                "sha256:f52fbd32b2b3b86ff88ef6c490628285f482af15ddcb29541f94bcf526a3f6c7"), is(none()));
+            assert_that(Secret::parse("sha256::f52fbd32"), is(none()));
+            assert_that(Secret::parse("sha256:NaCl:"), is(none()));
+        }
+
+        it "hashes to lowercase hex" {
+            assert_that(sha256_hex("hunter2"), is(equal_to(
+                "f52fbd32b2b3b86ff88ef6c490628285f482af15ddcb29541f94bcf526a3f6c7".to_string())));
+        }
+    }
+
+    describe! masks {
+        it "matches literally" {
+            assert_that(mask_matches("127.0.0.1", "127.0.0.1"), is(equal_to(true)));
+            assert_that(mask_matches("127.0.0.1", "127.0.0.10"), is(equal_to(false)));
+        }
+
+        it "matches wildcards" {
+            assert_that(mask_matches("*", "anything"), is(equal_to(true)));
+            assert_that(mask_matches("127.*", "127.0.0.1"), is(equal_to(true)));
+            assert_that(mask_matches("*.example.com", "irc.EXAMPLE.com"), is(equal_to(true)));
+            assert_that(mask_matches("10.0.0.?", "10.0.0.7"), is(equal_to(true)));
+            assert_that(mask_matches("10.0.0.?", "10.0.0.17"), is(equal_to(false)));
+            assert_that(mask_matches("*a*b", "xaxxb"), is(equal_to(true)));
+            assert_that(mask_matches("*a*b", "xaxxbx"), is(equal_to(false)));
+        }
+
+        it "selects classes by hostname" {
+            let class = ClientClass {
+                name: "local".to_string(),
+                hosts: vec!["127.*".to_string(), "::1".to_string()],
+                password: None,
+            };
+
+            assert_that(class.matches("::1"), is(equal_to(true)));
+            assert_that(class.matches("10.0.0.1"), is(equal_to(false)));
+        }
+    }
+}
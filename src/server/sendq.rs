@@ -0,0 +1,152 @@
+use std::io;
+use std::io::Write;
+
+/// Outgoing data of a single client, waiting for the socket to become
+/// writable.
+pub struct SendQ {
+    buf: Vec<u8>,
+    /// Offset of the first byte in `buf` which has not been written yet.
+    start: usize,
+    max_size: usize,
+}
+
+impl SendQ {
+    pub fn new(max_size: usize) -> SendQ {
+        SendQ {
+            buf: Vec::new(),
+            start: 0,
+            max_size: max_size,
+        }
+    }
+
+    pub fn len(&self) -> usize { self.buf.len() - self.start }
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    pub fn clear(&mut self) {
+        self.buf.clear();
+        self.start = 0;
+    }
+
+    /// Queues the data. Returns false and leaves the queue untouched if that
+    /// would go over the maximum size.
+    pub fn push(&mut self, data: &[u8]) -> bool {
+        if self.len() + data.len() > self.max_size {
+            false
+        } else {
+            self.buf.extend(data.iter().cloned());
+            true
+        }
+    }
+
+    /// Writes as much of the queue as the socket accepts without blocking.
+    pub fn flush_to(&mut self, socket: &mut Write) -> io::Result<()> {
+        let result = self.write_out(socket);
+        self.compact();
+        result
+    }
+
+    fn write_out(&mut self, socket: &mut Write) -> io::Result<()> {
+        while !self.is_empty() {
+            let result = socket.write(&self.buf[self.start..]);
+            match result {
+                Ok(0) => return Err(io::Error::new(io::ErrorKind::WriteZero,
+                    "failed to write queued data")),
+                Ok(len) => self.start += len,
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Drops the written bytes once they make up at least half of the
+    /// buffer, so the copying stays linear in the amount of data sent.
+    fn compact(&mut self) {
+        if self.is_empty() {
+            self.clear();
+        } else if self.start >= self.buf.len() / 2 {
+            self.buf = self.buf[self.start..].to_vec();
+            self.start = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    pub use std::io;
+    pub use super::SendQ;
+    pub use hamcrest::{assert_that, is, not, none, equal_to};
+
+    /// Accepts a limited amount of bytes, then blocks.
+    pub struct SlowSock { pub data: Vec<u8>, pub room: usize }
+
+    impl io::Write for SlowSock {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.room == 0 {
+                return Err(io::Error::new(io::ErrorKind::WouldBlock, "blocked"));
+            }
+            let len = if buf.len() < self.room { buf.len() } else { self.room };
+            self.data.extend(buf[..len].iter().cloned());
+            self.room -= len;
+            Ok(len)
+        }
+        fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    }
+
+    describe! sendq {
+        before_each {
+            let mut q = SendQ::new(16);
+            let mut sock = SlowSock { data: Vec::new(), room: 100 };
+        }
+
+        it "accepts data up to the maximum size" {
+            assert_that(q.push(b"0123456789"), is(equal_to(true)));
+            assert_that(q.push(b"012345"), is(equal_to(true)));
+            assert_that(q.len(), is(equal_to(16)));
+        }
+
+        it "refuses data over the maximum size" {
+            q.push(b"0123456789");
+            assert_that(q.push(b"0123456789"), is(equal_to(false)));
+            assert_that(q.len(), is(equal_to(10)));
+        }
+
+        it "flushes everything to a writable socket" {
+            q.push(b"PING x\r\n");
+            q.flush_to(&mut sock).ok().unwrap();
+
+            assert_that(q.is_empty(), is(equal_to(true)));
+            assert_that(sock.data, is(equal_to(b"PING x\r\n".to_vec())));
+        }
+
+        it "keeps the remainder when the socket blocks" {
+            sock.room = 4;
+            q.push(b"PING x\r\n");
+
+            assert_that(q.flush_to(&mut sock).is_ok(), is(equal_to(true)));
+            assert_that(q.len(), is(equal_to(4)));
+
+            sock.room = 100;
+            q.flush_to(&mut sock).ok().unwrap();
+            assert_that(sock.data, is(equal_to(b"PING x\r\n".to_vec())));
+        }
+
+        it "keeps the order over several partial writes" {
+            sock.room = 3;
+            q.push(b"PING x\r\n");
+            q.flush_to(&mut sock).ok().unwrap();
+            q.push(b"PING y\r\n");
+
+            assert_that(q.len(), is(equal_to(13)));
+
+            sock.room = 2;
+            q.flush_to(&mut sock).ok().unwrap();
+            sock.room = 100;
+            q.flush_to(&mut sock).ok().unwrap();
+
+            assert_that(q.is_empty(), is(equal_to(true)));
+            assert_that(sock.data, is(equal_to(b"PING x\r\nPING y\r\n".to_vec())));
+        }
+    }
+}